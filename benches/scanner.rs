@@ -0,0 +1,47 @@
+//! Rendimiento del scanner sobre `data/test_large.cat` repetido hasta ~1 MiB.
+//!
+//! `cargo bench` compara el texto ASCII (camino rápido) con el mismo texto con identificadores
+//! Unicode, que usan la máquina de estados completa.
+
+use std::{fs, hint::black_box};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use scanner::{options::LexerOptions, reader::ReaderLexer, tokenize_ref, tokenize_with};
+
+const TARGET_SIZE: usize = 1024 * 1024;
+
+fn scaled_source() -> String {
+    let sample = fs::read_to_string("data/test_large.cat").expect("falta data/test_large.cat");
+    sample.repeat(TARGET_SIZE / sample.len() + 1)
+}
+
+fn bench_tokenize(c: &mut Criterion) {
+    let ascii = scaled_source();
+    let unicode = ascii.replace("main", "señal");
+    let unicode_options = LexerOptions {
+        unicode_identifiers: true,
+        ..Default::default()
+    };
+    let inputs = [
+        ("ascii", &ascii, LexerOptions::default()),
+        ("unicode", &unicode, unicode_options),
+    ];
+
+    let mut group = c.benchmark_group("tokenize");
+    for (name, source, options) in inputs.iter() {
+        group.throughput(Throughput::Bytes(source.len() as u64));
+        group.bench_with_input(BenchmarkId::new("owned", name), source, |b, source| {
+            b.iter(|| tokenize_with(black_box(source), options))
+        });
+        group.bench_with_input(BenchmarkId::new("borrowed", name), source, |b, source| {
+            b.iter(|| tokenize_ref(black_box(source), options))
+        });
+        group.bench_with_input(BenchmarkId::new("reader", name), source, |b, source| {
+            b.iter(|| ReaderLexer::new(black_box(source.as_bytes()), options).count())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_tokenize);
+criterion_main!(benches);
@@ -1,7 +1,18 @@
 use std::{fs, io::Write, path::Path};
 
-use clap::{Args, Parser, Subcommand};
-use scanner::tokenize_file;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use scanner::{
+    brackets::check_brackets,
+    data::Severity,
+    dialect::Dialect,
+    encoding::{decode, SourceEncoding},
+    line_endings::{normalize_line_endings, LineEnding, SourceFormat, BOM},
+    options::LexerOptions,
+    positions::{PositionEncoding, DEFAULT_TAB_WIDTH},
+    read_file_bytes,
+    symbols::Interner,
+    tokenize_decoded_with_diagnostics,
+};
 
 #[derive(Parser)]
 #[command(version = env!("CARGO_PKG_VERSION"), author = "Ernesto Ramírez (https://github.com/ErnestoRB)", about = "CLI scanner for Vanilla Lang", long_about = None)]
@@ -26,6 +37,93 @@ enum Commands {
 #[derive(Args, Clone)]
 struct BuildArgs {
     files: Vec<String>,
+    #[arg(long, default_value_t = Dialect::Vanilla)]
+    /// Language dialect: vanilla, legacy or c-like. A first line such as
+    /// `// vanilla: dialect=legacy, nested-comments` overrides the flags for that file
+    dialect: Dialect,
+    #[arg(long)]
+    /// Treat a sign before a number as part of the literal when it can't be a binary operator
+    signed_numbers: bool,
+    #[arg(long)]
+    /// Allow block comments to be nested
+    nested_comments: bool,
+    #[arg(long)]
+    /// Emit runs of unknown characters as NONE tokens
+    none_tokens: bool,
+    #[arg(long)]
+    /// Accept && and || as aliases of and/or
+    c_operators: bool,
+    #[arg(long)]
+    /// Match keywords ignoring case, warning when they are not written in canonical case
+    case_insensitive_keywords: bool,
+    #[arg(long)]
+    /// Allow Unicode identifiers (UAX #31), warning about confusable or mixed-script names
+    unicode_identifiers: bool,
+    #[arg(short = 'W', long = "warn", value_name = "LINT")]
+    /// Enable a lexical warning: all, long-identifiers, keyword-case, mixed-indentation,
+    /// trailing-whitespace, leading-zeros or commented-out-code
+    warnings: Vec<String>,
+    #[arg(long)]
+    /// Maximum identifier length before warning (enables long-identifiers)
+    max_identifier_length: Option<usize>,
+    #[arg(long, value_enum, default_value_t = Columns::Chars)]
+    /// Unit of reported columns: chars, bytes, utf16 (LSP) or display (terminal cells)
+    columns: Columns,
+    #[arg(long, default_value_t = DEFAULT_TAB_WIDTH)]
+    /// Tab width for --columns display
+    tab_width: usize,
+    #[arg(long, default_value_t = SourceEncoding::Utf8)]
+    /// Source encoding: utf-8 (invalid bytes are reported as errors), latin1 or windows-1252
+    encoding: SourceEncoding,
+    #[arg(long, default_value_t = LineEnding::Lf)]
+    /// Line ending of the .lex output and of the --normalize copy: lf, crlf or cr
+    line_endings: LineEnding,
+    #[arg(long)]
+    /// Also write the source as <file>.normalized, without BOM and with every line ending
+    /// replaced by --line-endings
+    normalize: bool,
+    #[arg(long)]
+    /// Print how many times each identifier appears across all files
+    symbols: bool,
+    #[arg(long)]
+    /// Report unbalanced parentheses and braces with a suggested repair
+    brackets: bool,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum Columns {
+    Chars,
+    Bytes,
+    Utf16,
+    Display,
+}
+
+impl BuildArgs {
+    fn lexer_options(&self) -> Result<LexerOptions, String> {
+        let mut options = LexerOptions::for_dialect(self.dialect);
+        options.signed_numbers |= self.signed_numbers;
+        options.nested_comments |= self.nested_comments;
+        options.none_tokens |= self.none_tokens;
+        options.c_operators |= self.c_operators;
+        options.case_insensitive_keywords |= self.case_insensitive_keywords;
+        options.unicode_identifiers |= self.unicode_identifiers;
+        for lint in self.warnings.iter() {
+            options.lints.enable(lint)?;
+        }
+        if self.max_identifier_length.is_some() {
+            options.lints.max_identifier_length = self.max_identifier_length;
+        }
+        options.encoding = self.encoding;
+        options.position_encoding = match self.columns {
+            Columns::Chars => PositionEncoding::Chars,
+            Columns::Bytes => PositionEncoding::Bytes,
+            Columns::Utf16 => PositionEncoding::Utf16,
+            Columns::Display => PositionEncoding::Display {
+                tab_width: self.tab_width,
+            },
+        };
+        Ok(options)
+    }
 }
 
 fn main() {
@@ -35,16 +133,48 @@ fn main() {
     // matches just as you would the top level cmd
     match &cli.command {
         Commands::Build(args) => {
+            let options = match args.lexer_options() {
+                Ok(options) => options,
+                Err(e) => {
+                    eprintln!("ERROR: {}", e);
+                    std::process::exit(2);
+                }
+            };
+            let mut interner = Interner::new();
             for file in args.files.iter() {
                 println!("{}", file);
-                let tokenization = tokenize_file(&file);
-                if let Ok(res) = tokenization {
+                let tokenization = read_file_bytes(file).map(|bytes| {
+                    let decoded = decode(&bytes, options.encoding);
+                    let res = tokenize_decoded_with_diagnostics(&decoded, &options);
+                    (res, SourceFormat::detect(&decoded.text), decoded.text)
+                });
+                if let Ok((mut res, format, text)) = tokenization {
+                    if args.brackets {
+                        res.1.extend(check_brackets(&res.0));
+                        res.1.sort_by_key(|err| (err.start.lin, err.start.col));
+                    }
                     if cli.verbose {
                         println!(
-                            "[VERBOSE] Tokenizing {}: {} Tokens, {} Errors",
+                            "[VERBOSE] Line endings of {}: {}{}{}",
+                            file,
+                            format
+                                .line_ending()
+                                .map_or("none".to_string(), |ending| ending.to_string()),
+                            if format.is_mixed() { " (mixed)" } else { "" },
+                            if format.bom { ", UTF-8 BOM" } else { "" }
+                        );
+                        println!(
+                            "[VERBOSE] Tokenizing {}: {} Tokens, {} Errors, {} Warnings",
                             file,
                             res.0.len(),
-                            res.1.len()
+                            res.1
+                                .iter()
+                                .filter(|e| e.severity == Severity::Error)
+                                .count(),
+                            res.1
+                                .iter()
+                                .filter(|e| e.severity == Severity::Warning)
+                                .count()
                         );
                     }
                     if !cli.no_save {
@@ -59,10 +189,15 @@ fn main() {
                             if let Ok(mut file_handle) = fs::File::create(output_file.clone()) {
                                 for token in res.0.iter() {
                                     // Imprimir tokens
-                                    if let Err(_) = file_handle.write_fmt(format_args!(
-                                        "{:?}, {}\n",
-                                        token.token_type, token.lexemme
-                                    )) {
+                                    if file_handle
+                                        .write_fmt(format_args!(
+                                            "{:?}, {}{}",
+                                            token.token_type,
+                                            token.lexemme,
+                                            args.line_endings.as_str()
+                                        ))
+                                        .is_err()
+                                    {
                                         eprintln!(
                                             "ERROR: Could not write to {}",
                                             output_file.to_str().unwrap()
@@ -72,14 +207,35 @@ fn main() {
                             } else {
                                 eprintln!("ERROR: Could not create file {}", file);
                             }
+                            if args.normalize {
+                                let normalized_file = output_file.with_extension("normalized");
+                                let text = text.strip_prefix(BOM).unwrap_or(&text);
+                                if fs::write(
+                                    &normalized_file,
+                                    normalize_line_endings(text, args.line_endings),
+                                )
+                                .is_err()
+                                {
+                                    eprintln!(
+                                        "ERROR: Could not write to {}",
+                                        normalized_file.to_str().unwrap()
+                                    );
+                                }
+                            }
                         } else {
                             eprintln!("ERROR: Could not save file {} due invalid filename", file);
                         }
                     }
 
                     for err in res.1.iter() {
+                        let level = match err.severity {
+                            Severity::Error => "ERROR",
+                            Severity::Warning => "WARNING",
+                        };
                         eprintln!(
-                            "ERROR: ({}, [({}, {})-({},{})]): {} ",
+                            "{}[{}]: ({}, [({}, {})-({},{})]): {} ",
+                            level,
+                            err.code,
                             file,
                             err.start.col,
                             err.start.lin,
@@ -87,11 +243,32 @@ fn main() {
                             err.end.lin,
                             err.message,
                         );
+                        if let Some(suggestion) = &err.suggestion {
+                            eprintln!(
+                                "HELP: [({}, {})-({},{})]: {}",
+                                suggestion.start.col,
+                                suggestion.start.lin,
+                                suggestion.end.col,
+                                suggestion.end.lin,
+                                suggestion.message,
+                            );
+                        }
+                    }
+                    if args.symbols {
+                        interner.intern_tokens(res.0);
                     }
                 } else if let Err(e) = tokenization {
                     eprintln!("ERROR: Could not generate output for {}: {}", file, e)
                 }
             }
+            if args.symbols {
+                let mut occurrences: Vec<_> = interner.occurrences().collect();
+                occurrences.sort_by(|a, b| b.2.cmp(&a.2).then(a.1.cmp(b.1)));
+                println!("Identifiers: {}", occurrences.len());
+                for (_, name, count) in occurrences {
+                    println!("{:>8} {}", count, name);
+                }
+            }
         }
     }
 }
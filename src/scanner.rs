@@ -1,20 +1,104 @@
+pub mod ascii;
+pub mod brackets;
+pub mod codes;
 pub mod data;
+pub mod dialect;
+pub mod directives;
+pub mod encoding;
+pub mod incremental;
+pub mod line_endings;
+pub mod lines;
+pub mod lints;
+pub mod options;
+pub mod positions;
+pub mod pragmas;
+pub mod reader;
+pub mod stream;
+pub mod suggestions;
+pub mod symbols;
+pub mod unicode;
 pub mod utils;
 
-use std::{fs::File, io::Read};
+use std::{borrow::Cow, fs};
 
 use data::*;
+use directives::header_options;
+use encoding::{
+    decode, invalid_sequence_errors, without_replaced_symbols, Decoded, InvalidSequence,
+};
+use line_endings::{mixed_line_ending_warnings, BOM};
+use lints::{line_warnings, token_warnings};
+use options::{LexerOptions, LintOptions};
+use positions::{ColumnMap, PositionEncoding};
+use pragmas::Suppressions;
+use suggestions::{covers, keyword_warning, suggest, KEYWORD_SUGGESTIONS};
+use symbols::{InternedToken, Interner};
+use unicode::{mixed_script_warning, ConfusableIdentifiers};
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+use unicode_xid::UnicodeXID;
 use utils::*;
 
-pub fn get_token<'a>(mut text: &'a str, cursor: &mut Cursor) -> (Result<Token, Error>, &'a str) {
+pub fn get_token<'a>(text: &'a str, cursor: &mut Cursor) -> (Result<Token, Error>, &'a str) {
+    get_token_with(text, cursor, &LexerOptions::default(), None)
+}
+
+/// Igual que `get_token` pero respetando `options`. `prev` es el último token significativo
+/// (sin comentarios) leído, necesario para decidir si un signo pertenece al número siguiente.
+pub fn get_token_with<'a>(
+    text: &'a str,
+    cursor: &mut Cursor,
+    options: &LexerOptions,
+    prev: Option<&TokenType>,
+) -> (Result<Token, Error>, &'a str) {
+    let (result, rest) = get_token_ref(text, cursor, options, prev);
+    (result.map(Token::from), rest)
+}
+
+/// Rango en bytes del lexema dentro del texto. Los caracteres guardados de un token siempre son
+/// contiguos, así que el lexema se puede tomar prestado del texto en lugar de copiarlo.
+struct Span {
+    start: Option<usize>,
+    end: usize,
+}
+
+impl Span {
+    fn push(&mut self, offset: usize, c: char) {
+        self.start.get_or_insert(offset);
+        self.end = offset + c.len_utf8();
+    }
+
+    fn of<'a>(&self, source: &'a str) -> &'a str {
+        self.start.map_or("", |start| &source[start..self.end])
+    }
+}
+
+/// Como `get_token_with`, pero el lexema se toma prestado de `text` (solo los identificadores que
+/// cambian al normalizarse a NFC se copian)
+pub fn get_token_ref<'a>(
+    mut text: &'a str,
+    cursor: &mut Cursor,
+    options: &LexerOptions,
+    prev: Option<&TokenType>,
+) -> (Result<TokenRef<'a>, Error>, &'a str) {
+    let source = text;
+    text = ascii::skip_whitespace(text, cursor);
+    if let Some((token, rest)) =
+        ascii::scan_word(text, cursor, options).or_else(|| ascii::scan_line_comment(text, cursor))
+    {
+        return (Ok(token), rest);
+    }
     let mut state: State = State::START;
-    let mut result = String::new();
+    let mut result = Span {
+        start: None,
+        end: 0,
+    };
     let mut result_token: TokenType = TokenType::EOF;
     let mut char: char = ' ';
     let mut save: bool;
     let mut consume: bool;
     let mut eof = false;
     let mut start = cursor.clone();
+    let mut comment_openers: Vec<Cursor> = Vec::new();
     while !matches!(state, State::DONE) {
         save = false;
         consume = true;
@@ -25,23 +109,17 @@ pub fn get_token<'a>(mut text: &'a str, cursor: &mut Cursor) -> (Result<Token, E
                 match state {
                     State::START => {
                         if ['\r', '\n', '\t', ' '].contains(&c) {
-                            if c == '\n' {
-                                cursor.lin += 1;
-                                cursor.col = 1;
-                            } else if c == '\r' {
-                                if cursor.col >= 2 {
-                                    // Se comprueba que sea col 2 y no col 1 (inicio de linea) porque ya subio uno en la linea superior
-                                    // antes del match
-                                    cursor.col -= 1;
-                                }
-                            }
+                            advance_line_break(c, text, cursor);
                             start = cursor.clone();
                             save = false;
-                        } else if c.is_digit(10) {
+                        } else if c.is_ascii_digit() {
                             state = State::NUM;
                             result_token = TokenType::INT;
                             save = true;
-                        } else if c.is_ascii_alphabetic() || c == '_' {
+                        } else if c.is_ascii_alphabetic()
+                            || c == '_'
+                            || (options.unicode_identifiers && c.is_xid_start())
+                        {
                             state = State::ID;
                             result_token = TokenType::ID;
                             save = true;
@@ -73,11 +151,40 @@ pub fn get_token<'a>(mut text: &'a str, cursor: &mut Cursor) -> (Result<Token, E
                             save = true;
                             state = State::EQ;
                             result_token = TokenType::ASSIGN;
+                        } else if c == '.' && text[1..].starts_with(|d: char| d.is_ascii_digit()) {
+                            // un '.' no puede empezar un número; en "34.34.34" no es un DOT
+                            let error_cursor = cursor.clone();
+                            return (
+                                Err(Error {
+                                    message:
+                                        "Número mal formado: el '.' no puede ir antes de un dígito"
+                                            .to_string(),
+                                    start,
+                                    end: error_cursor,
+                                    lexemme: c.to_string(),
+                                    suggestion: None,
+                                    severity: Severity::Error,
+                                    code: codes::MALFORMED_NUMBER.to_string(),
+                                }),
+                                &text[1..],
+                            );
                         } else {
                             state = State::DONE;
                             save = true;
-                            if let Some(token) = SYMBOLS.get(c.to_string().as_str()) {
+                            if let Some(token) = options.config.symbol(c) {
                                 result_token = token.clone();
+                                if matches!(result_token, TokenType::COLON) {
+                                    state = State::COLON;
+                                } else if compound_assignment(token).is_some() {
+                                    state = State::OPERATOR;
+                                }
+                            } else if options.c_operators && (c == '&' || c == '|') {
+                                // si no le sigue el mismo simbolo no es un operador
+                                state = if c == '&' { State::AMP } else { State::PIPE };
+                                result_token = TokenType::NONE;
+                            } else if options.none_tokens {
+                                state = State::NONE;
+                                result_token = TokenType::NONE;
                             } else {
                                 let error_cursor = cursor.clone();
                                 return (
@@ -86,6 +193,9 @@ pub fn get_token<'a>(mut text: &'a str, cursor: &mut Cursor) -> (Result<Token, E
                                         start,
                                         end: error_cursor,
                                         lexemme: c.to_string(),
+                                        suggestion: None,
+                                        severity: Severity::Error,
+                                        code: codes::UNKNOWN_SYMBOL.to_string(),
                                     }),
                                     &text[char.len_utf8()..],
                                 );
@@ -112,6 +222,53 @@ pub fn get_token<'a>(mut text: &'a str, cursor: &mut Cursor) -> (Result<Token, E
                         } else if c == '*' {
                             save = true;
                             state = State::BLOCK_COM_1;
+                        } else if c == '=' {
+                            save = true;
+                            state = State::DONE;
+                            result_token = TokenType::DIV_ASSIGN;
+                        } else {
+                            save = false;
+                            consume = false;
+                            state = State::DONE;
+                        }
+                    }
+                    State::OPERATOR => {
+                        match compound_assignment(&result_token) {
+                            Some(compound) if c == '=' => {
+                                save = true;
+                                result_token = compound;
+                            }
+                            _ => {
+                                save = false;
+                                consume = false;
+                            }
+                        }
+                        state = State::DONE;
+                    }
+                    State::COLON => {
+                        if c == '=' {
+                            // ":=" no existe en Vanilla; se toma completo para poder sugerir '='
+                            if options.none_tokens {
+                                save = true;
+                                state = State::DONE;
+                                result_token = TokenType::NONE;
+                            } else {
+                                result.push(source.len() - text.len(), c);
+                                let error_cursor = cursor.clone();
+                                let lexemme = result.of(source);
+                                return (
+                                    Err(Error {
+                                        message: format!("Simbolo '{}' no permitido", lexemme),
+                                        start,
+                                        end: error_cursor,
+                                        lexemme: lexemme.to_string(),
+                                        suggestion: None,
+                                        severity: Severity::Error,
+                                        code: codes::UNKNOWN_SYMBOL.to_string(),
+                                    }),
+                                    &text[c.len_utf8()..],
+                                );
+                            }
                         } else {
                             save = false;
                             consume = false;
@@ -119,7 +276,7 @@ pub fn get_token<'a>(mut text: &'a str, cursor: &mut Cursor) -> (Result<Token, E
                         }
                     }
                     State::NUM => {
-                        if c.is_digit(10) {
+                        if c.is_ascii_digit() {
                             save = true;
                             result_token = TokenType::INT;
                         } else if c == '.' {
@@ -133,7 +290,10 @@ pub fn get_token<'a>(mut text: &'a str, cursor: &mut Cursor) -> (Result<Token, E
                         }
                     }
                     State::ID => {
-                        if c == '_' || c.is_ascii_alphabetic() || c.is_digit(10) {
+                        if c == '_'
+                            || c.is_ascii_alphanumeric()
+                            || (options.unicode_identifiers && c.is_xid_continue())
+                        {
                             save = true;
                             result_token = TokenType::ID;
                         } else {
@@ -179,7 +339,7 @@ pub fn get_token<'a>(mut text: &'a str, cursor: &mut Cursor) -> (Result<Token, E
                         }
                     }
                     State::LINE_COM => {
-                        if c == '\n' {
+                        if c == '\n' || c == '\r' {
                             save = false;
                             consume = false;
                             state = State::DONE;
@@ -193,32 +353,89 @@ pub fn get_token<'a>(mut text: &'a str, cursor: &mut Cursor) -> (Result<Token, E
                         if c == '*' {
                             save = true;
                             state = State::BLOCK_COM_2;
+                        } else if c == '/' && options.nested_comments {
+                            save = true;
+                            state = State::BLOCK_COM_SLASH;
                         } else {
                             save = true;
-                            if c == '\n' {
-                                cursor.lin += 1;
-                                cursor.col = 1;
-                            }
+                            advance_line_break(c, text, cursor);
                         }
                     }
                     State::BLOCK_COM_2 => {
                         if c == '/' {
                             save = true;
-                            state = State::DONE;
-                            result_token = TokenType::BLOCK_COMMENT;
+                            if comment_openers.pop().is_some() {
+                                // se cerro un comentario anidado, seguimos dentro del exterior
+                                state = State::BLOCK_COM_1;
+                            } else {
+                                state = State::DONE;
+                                result_token = TokenType::BLOCK_COMMENT;
+                            }
                         } else if c == '*' {
                             save = true;
                         } else {
-                            if c == '\n' {
-                                cursor.lin += 1;
-                                cursor.col = 1;
-                            }
+                            advance_line_break(c, text, cursor);
                             save = true;
                             state = State::BLOCK_COM_1;
                         }
                     }
+                    State::BLOCK_COM_SLASH => {
+                        save = true;
+                        if c == '*' {
+                            // el '/' esta una columna antes del '*'
+                            let mut opener = cursor.clone();
+                            opener.col -= 2;
+                            comment_openers.push(opener);
+                            state = State::BLOCK_COM_1;
+                        } else if c != '/' {
+                            advance_line_break(c, text, cursor);
+                            state = State::BLOCK_COM_1;
+                        }
+                    }
+                    State::NONE => {
+                        if is_unknown_symbol(c, options) {
+                            save = true;
+                        } else {
+                            save = false;
+                            consume = false;
+                            state = State::DONE;
+                        }
+                    }
+                    State::AMP | State::PIPE => {
+                        if result.of(source).ends_with(c) {
+                            save = true;
+                            state = State::DONE;
+                            result_token = if c == '&' {
+                                TokenType::AND
+                            } else {
+                                TokenType::OR
+                            };
+                        } else if options.none_tokens {
+                            save = false;
+                            consume = false;
+                            state = State::NONE;
+                        } else {
+                            cursor.col -= 1;
+                            let error_cursor = cursor.clone();
+                            return (
+                                Err(Error {
+                                    message: format!(
+                                        "Simbolo '{}' no permitido",
+                                        result.of(source)
+                                    ),
+                                    start,
+                                    end: error_cursor,
+                                    lexemme: result.of(source).to_string(),
+                                    suggestion: None,
+                                    severity: Severity::Error,
+                                    code: codes::UNKNOWN_SYMBOL.to_string(),
+                                }),
+                                text,
+                            );
+                        }
+                    }
                     State::FLOAT_DOT => {
-                        if c.is_digit(10) {
+                        if c.is_ascii_digit() {
                             save = true;
                             state = State::FLOAT;
                             result_token = TokenType::FLOAT;
@@ -232,14 +449,17 @@ pub fn get_token<'a>(mut text: &'a str, cursor: &mut Cursor) -> (Result<Token, E
                                     message:
                                         "Un número flotante debe tener números después del '.'"
                                             .to_string(),
-                                    lexemme: result,
+                                    lexemme: result.of(source).to_string(),
+                                    suggestion: None,
+                                    severity: Severity::Error,
+                                    code: codes::INCOMPLETE_FLOAT.to_string(),
                                 }),
-                                &text[..],
+                                text,
                             );
                         }
                     }
                     State::FLOAT => {
-                        if c.is_digit(10) {
+                        if c.is_ascii_digit() {
                             save = true;
                         } else {
                             state = State::DONE;
@@ -252,6 +472,17 @@ pub fn get_token<'a>(mut text: &'a str, cursor: &mut Cursor) -> (Result<Token, E
                             result_token = TokenType::DEC;
                             state = State::DONE;
                             save = true;
+                        } else if c == '=' {
+                            result_token = TokenType::MIN_ASSIGN;
+                            state = State::DONE;
+                            save = true;
+                        } else if c.is_ascii_digit()
+                            && options.signed_numbers
+                            && !prev.is_some_and(can_end_expression)
+                        {
+                            save = true;
+                            state = State::NUM;
+                            result_token = TokenType::INT;
                         } else {
                             save = false;
                             consume = false;
@@ -264,6 +495,17 @@ pub fn get_token<'a>(mut text: &'a str, cursor: &mut Cursor) -> (Result<Token, E
                             result_token = TokenType::INC;
                             state = State::DONE;
                             save = true;
+                        } else if c == '=' {
+                            result_token = TokenType::SUM_ASSIGN;
+                            state = State::DONE;
+                            save = true;
+                        } else if c.is_ascii_digit()
+                            && options.signed_numbers
+                            && !prev.is_some_and(can_end_expression)
+                        {
+                            save = true;
+                            state = State::NUM;
+                            result_token = TokenType::INT;
                         } else {
                             save = false;
                             consume = false;
@@ -287,19 +529,44 @@ pub fn get_token<'a>(mut text: &'a str, cursor: &mut Cursor) -> (Result<Token, E
                             start,
                             end: new_cursor,
                             message: "Los numeros flotantes deben ser seguidos de un número después del punto".to_string(),
-                            lexemme: result
+                            lexemme: result.of(source).to_string(),
+                            suggestion: None,
+                            severity: Severity::Error,
+                            code: codes::INCOMPLETE_FLOAT.to_string(),
                         }),
                         text,
                     );
                 }
-                if matches!(state, State::BLOCK_COM_1) || matches!(state, State::BLOCK_COM_2) {
+                if matches!(state, State::AMP | State::PIPE) && !options.none_tokens {
                     let new_cursor = cursor.clone();
                     return (
                         Err(Error {
+                            message: format!("Simbolo '{}' no permitido", result.of(source)),
                             start,
                             end: new_cursor,
+                            lexemme: result.of(source).to_string(),
+                            suggestion: None,
+                            severity: Severity::Error,
+                            code: codes::UNKNOWN_SYMBOL.to_string(),
+                        }),
+                        text,
+                    );
+                }
+                if matches!(
+                    state,
+                    State::BLOCK_COM_1 | State::BLOCK_COM_2 | State::BLOCK_COM_SLASH
+                ) {
+                    let new_cursor = cursor.clone();
+                    return (
+                        Err(Error {
+                            // se reporta el comentario abierto mas interno
+                            start: comment_openers.pop().unwrap_or(start),
+                            end: new_cursor,
                             message: "El comentario no fue terminado correctamente".to_string(),
-                            lexemme: result,
+                            lexemme: result.of(source).to_string(),
+                            suggestion: None,
+                            severity: Severity::Error,
+                            code: codes::UNTERMINATED_COMMENT.to_string(),
                         }),
                         text,
                     );
@@ -308,7 +575,7 @@ pub fn get_token<'a>(mut text: &'a str, cursor: &mut Cursor) -> (Result<Token, E
             }
         }
         if save {
-            result.push(char);
+            result.push(source.len() - text.len(), char);
         }
         if consume {
             text = &text[char.len_utf8()..];
@@ -319,15 +586,26 @@ pub fn get_token<'a>(mut text: &'a str, cursor: &mut Cursor) -> (Result<Token, E
         }
     }
     if matches!(state, State::DONE) {
+        let mut lexemme = Cow::Borrowed(result.of(source));
         if matches!(result_token, TokenType::ID) {
-            result_token = reserved_lookup(result.as_str());
+            if !lexemme.is_ascii() && !is_nfc(&lexemme) {
+                // UAX #31: los identificadores se comparan en forma NFC
+                lexemme = Cow::Owned(lexemme.nfc().collect());
+            }
+            result_token = ascii::identifier_type(&lexemme, options);
+        } else if matches!(
+            result_token,
+            TokenType::BLOCK_COMMENT | TokenType::INLINE_COMMENT
+        ) && is_doc_comment(&lexemme)
+        {
+            result_token = TokenType::DOC_COMMENT;
         }
         return (
-            Ok(Token {
+            Ok(TokenRef {
                 token_type: result_token,
                 start,
                 end: cursor.clone(),
-                lexemme: result,
+                lexemme,
             }),
             text,
         );
@@ -337,50 +615,271 @@ pub fn get_token<'a>(mut text: &'a str, cursor: &mut Cursor) -> (Result<Token, E
             message: "Unexpected error".to_string(),
             start,
             end: cursor.clone(),
-            lexemme: result,
+            lexemme: result.of(source).to_string(),
+            suggestion: None,
+            severity: Severity::Error,
+            code: codes::UNEXPECTED.to_string(),
         }),
         text,
     )
 }
 
 pub fn tokenize(contents: &str) -> (Vec<Token>, Vec<Error>) {
+    tokenize_with(contents, &LexerOptions::default())
+}
+
+pub fn tokenize_with(contents: &str, options: &LexerOptions) -> (Vec<Token>, Vec<Error>) {
+    let scan = scan(contents, &[], options);
+    (owned(scan.tokens), hard_errors(scan.diagnostics))
+}
+
+/// Como `tokenize_with`, pero los lexemas se toman prestados de `contents` en lugar de copiarse
+pub fn tokenize_ref<'a>(
+    contents: &'a str,
+    options: &LexerOptions,
+) -> (Vec<TokenRef<'a>>, Vec<Error>) {
+    let scan = scan(contents, &[], options);
+    (scan.tokens, hard_errors(scan.diagnostics))
+}
+
+/// Como `tokenize_with`, pero cada `ID` lleva su `Symbol` en `interner`, que se puede compartir
+/// entre archivos
+pub fn tokenize_interned(
+    contents: &str,
+    options: &LexerOptions,
+    interner: &mut Interner,
+) -> (Vec<InternedToken>, Vec<Error>) {
+    let (tokens, errors) = tokenize_with(contents, options);
+    (interner.intern_tokens(tokens), errors)
+}
+
+/// Como `tokenize_with`, pero además regresa los comentarios de documentación (`/** */` y `///`)
+/// asociados al token significativo que les sigue. Varios comentarios seguidos se unen en uno.
+pub fn tokenize_with_docs(
+    contents: &str,
+    options: &LexerOptions,
+) -> (Vec<Token>, Vec<Error>, Vec<DocComment>) {
+    let scan = scan(contents, &[], options);
+    (owned(scan.tokens), hard_errors(scan.diagnostics), scan.docs)
+}
+
+/// Como `tokenize_with`, pero la lista incluye también las advertencias (`Severity::Warning`),
+/// en el orden en que aparecen en el texto.
+pub fn tokenize_with_diagnostics(
+    contents: &str,
+    options: &LexerOptions,
+) -> (Vec<Token>, Vec<Error>) {
+    let scan = scan(contents, &[], options);
+    (owned(scan.tokens), scan.diagnostics)
+}
+
+fn hard_errors(diagnostics: Vec<Error>) -> Vec<Error> {
+    diagnostics
+        .into_iter()
+        .filter(|diagnostic| matches!(diagnostic.severity, Severity::Error))
+        .collect()
+}
+
+struct Scan<'a> {
+    tokens: Vec<TokenRef<'a>>,
+    diagnostics: Vec<Error>,
+    docs: Vec<DocComment>,
+}
+
+fn owned(tokens: Vec<TokenRef>) -> Vec<Token> {
+    tokens.into_iter().map(Token::from).collect()
+}
+
+/// Escanea `contents`; `invalid` son las secuencias que no se pudieron decodificar y que se
+/// reportan en lugar del U+FFFD que las reemplaza
+fn scan<'a>(contents: &'a str, invalid: &[InvalidSequence], options: &LexerOptions) -> Scan<'a> {
+    // el BOM no es parte del texto ni cuenta como columna
+    let original_len = contents.len();
+    let contents = contents.strip_prefix(BOM).unwrap_or(contents);
+    let (options, mut diagnostics) = header_options(contents, options);
+    let options = &options;
     let mut cursor = init_cursor();
-    let mut errors: Vec<Error> = Vec::new();
-    let mut tokens: Vec<Token> = Vec::new();
-    let text = contents.to_owned();
-    let mut rem_text = &text[..];
+    // el código típico tiene un token cada 3 a 8 bytes; reservar evita copiar el vector al crecer
+    let mut tokens: Vec<TokenRef> = Vec::with_capacity(contents.len() / 8);
+    let mut docs: Vec<DocComment> = Vec::new();
+    let mut pending_doc: Option<DocComment> = None;
+    let mut confusables = ConfusableIdentifiers::default();
+    let mut suppressions = Suppressions::default();
+    let mut rem_text = contents;
     loop {
-        let (result, string) = get_token(&rem_text, &mut cursor);
+        let prev = tokens.last().map(|t| &t.token_type);
+        let (result, string) = get_token_ref(rem_text, &mut cursor, options, prev);
         rem_text = string;
         match result {
             Ok(tkn) => {
                 if matches!(tkn.token_type, TokenType::EOF) {
                     break;
                 }
+                if matches!(tkn.token_type, TokenType::DOC_COMMENT) {
+                    let doc_text = doc_comment_text(&tkn.lexemme);
+                    match pending_doc.as_mut() {
+                        Some(doc) => {
+                            doc.text.push('\n');
+                            doc.text.push_str(&doc_text);
+                            doc.end = tkn.end;
+                        }
+                        None => {
+                            pending_doc = Some(DocComment {
+                                text: doc_text,
+                                start: tkn.start,
+                                end: tkn.end,
+                                token: None,
+                            })
+                        }
+                    }
+                    continue;
+                }
+                if options.lints != LintOptions::default() {
+                    diagnostics.extend(token_warnings(&tkn.to_token(), options));
+                }
                 if matches!(tkn.token_type, TokenType::BLOCK_COMMENT)
                     || matches!(tkn.token_type, TokenType::INLINE_COMMENT)
                 {
+                    diagnostics.extend(suppressions.add_comment(&tkn));
                     continue;
                 }
+                if matches!(tkn.token_type, TokenType::NONE) {
+                    push_error(
+                        &mut diagnostics,
+                        unknown_symbols_error(&tkn.to_token()),
+                        rem_text,
+                    );
+                }
+                if matches!(tkn.token_type, TokenType::ID)
+                    && KEYWORD_SUGGESTIONS.contains_key(&*tkn.lexemme)
+                {
+                    diagnostics.extend(keyword_warning(&tkn.to_token(), &options.config));
+                }
+                // las palabras reservadas son palabras, así que solo se revisan los ID y las
+                // palabras reservadas que se reconocieron sin importar mayúsculas
+                if (options.case_insensitive_keywords || options.lints.keyword_case)
+                    && tkn
+                        .lexemme
+                        .starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                {
+                    diagnostics.extend(keyword_case_warning(&tkn, &options.config));
+                }
+                if options.unicode_identifiers {
+                    let owned = tkn.to_token();
+                    diagnostics.extend(mixed_script_warning(&owned));
+                    diagnostics.extend(confusables.check(&owned));
+                }
+                if let Some(mut doc) = pending_doc.take() {
+                    doc.token = Some(tokens.len());
+                    docs.push(doc);
+                }
                 tokens.push(tkn);
             }
-            Err(err) => errors.push(err),
+            Err(err) => push_error(&mut diagnostics, err, rem_text),
         }
     }
-    (tokens, errors)
+    // documentación al final del archivo, sin token que documentar
+    docs.extend(pending_doc);
+    let mut line_diagnostics = line_warnings(contents, &options.lints);
+    line_diagnostics.extend(mixed_line_ending_warnings(contents));
+    let encoding_errors = invalid_sequence_errors(contents, invalid, original_len - contents.len());
+    if !encoding_errors.is_empty() {
+        // el U+FFFD ya se reporta como secuencia inválida, no como símbolo desconocido
+        let replaced = |cursor: &Cursor| encoding_errors.iter().any(|error| error.start == *cursor);
+        diagnostics = diagnostics
+            .into_iter()
+            .filter_map(|diagnostic| without_replaced_symbols(diagnostic, &replaced))
+            .collect();
+        line_diagnostics.extend(encoding_errors);
+    }
+    if !line_diagnostics.is_empty() {
+        diagnostics.extend(line_diagnostics);
+        diagnostics.sort_by_key(|diagnostic| (diagnostic.start.lin, diagnostic.start.col));
+    }
+    diagnostics.retain(|diagnostic| !suppressions.is_suppressed(diagnostic));
+    // las columnas se calculan en caracteres y se convierten al final
+    if options.position_encoding != PositionEncoding::Chars {
+        let columns = ColumnMap::new(contents, options.position_encoding);
+        tokens
+            .iter_mut()
+            .for_each(|token| columns.convert_token(token));
+        diagnostics
+            .iter_mut()
+            .for_each(|error| columns.convert_error(error));
+        docs.iter_mut().for_each(|doc| columns.convert_doc(doc));
+    }
+    Scan {
+        tokens,
+        diagnostics,
+        docs,
+    }
 }
 
-pub fn tokenize_file(file: &str) -> Result<(Vec<Token>, Vec<Error>), String> {
-    let f = File::open(file);
-    if let Ok(mut handle) = f {
-        let mut buffer: String = String::new();
-        match handle.read_to_string(&mut buffer) {
-            Ok(_) => Ok(tokenize(&mut buffer)),
-            Err(_e) => Err("El archivo no está en codificación UTF-8".to_string()),
-        }
-    } else {
-        Err(format!("Could not load file '{}'", file).to_string())
+/// Agrega el error con una sugerencia de corrección, salvo que el error anterior ya proponga
+/// reemplazar este mismo texto (como el segundo '&' de "&&")
+fn push_error(errors: &mut Vec<Error>, error: Error, rest: &str) {
+    let error = with_suggestion(errors.last(), error, rest);
+    errors.push(error);
+}
+
+/// Agrega la sugerencia de corrección al error si la del error anterior (`last`) no la cubre
+pub(crate) fn with_suggestion(last: Option<&Error>, mut error: Error, rest: &str) -> Error {
+    let covered = last
+        .and_then(|last| last.suggestion.as_ref())
+        .is_some_and(|suggestion| covers(suggestion, &error.start));
+    if !covered {
+        error.suggestion = suggest(&error, rest);
     }
+    error
+}
+
+/// Tokeniza texto en la codificación `options.encoding`. Nunca falla por la codificación: en UTF-8
+/// cada secuencia inválida se reporta como un error en su posición y se sigue escaneando.
+pub fn tokenize_bytes_with(bytes: &[u8], options: &LexerOptions) -> (Vec<Token>, Vec<Error>) {
+    let decoded = decode(bytes, options.encoding);
+    let scan = scan(&decoded.text, &decoded.invalid, options);
+    (owned(scan.tokens), hard_errors(scan.diagnostics))
+}
+
+/// Como `tokenize_bytes_with`, pero incluyendo advertencias (ver `tokenize_with_diagnostics`)
+pub fn tokenize_bytes_with_diagnostics(
+    bytes: &[u8],
+    options: &LexerOptions,
+) -> (Vec<Token>, Vec<Error>) {
+    tokenize_decoded_with_diagnostics(&decode(bytes, options.encoding), options)
+}
+
+/// Como `tokenize_bytes_with_diagnostics`, para texto ya decodificado con `decode`
+pub fn tokenize_decoded_with_diagnostics(
+    decoded: &Decoded,
+    options: &LexerOptions,
+) -> (Vec<Token>, Vec<Error>) {
+    let scan = scan(&decoded.text, &decoded.invalid, options);
+    (owned(scan.tokens), scan.diagnostics)
+}
+
+pub fn tokenize_file(file: &str) -> Result<(Vec<Token>, Vec<Error>), String> {
+    tokenize_file_with(file, &LexerOptions::default())
+}
+
+pub fn tokenize_file_with(
+    file: &str,
+    options: &LexerOptions,
+) -> Result<(Vec<Token>, Vec<Error>), String> {
+    read_file_bytes(file).map(|bytes| tokenize_bytes_with(&bytes, options))
+}
+
+/// Como `tokenize_file_with`, pero incluyendo advertencias (ver `tokenize_with_diagnostics`)
+pub fn tokenize_file_with_diagnostics(
+    file: &str,
+    options: &LexerOptions,
+) -> Result<(Vec<Token>, Vec<Error>), String> {
+    read_file_bytes(file).map(|bytes| tokenize_bytes_with_diagnostics(&bytes, options))
+}
+
+/// Lee el archivo sin decodificarlo; solo falla si no se puede abrir
+pub fn read_file_bytes(file: &str) -> Result<Vec<u8>, String> {
+    fs::read(file).map_err(|_| format!("Could not load file '{}'", file))
 }
 
 #[cfg(test)]
@@ -388,9 +887,11 @@ pub mod tests {
 
     use crate::{
         data::{Cursor, Token, TokenType},
-        get_token,
+        dialect::Dialect,
+        get_token, get_token_with,
+        options::{LexerConfig, LexerOptions},
         scanner::reserved_lookup,
-        utils::init_cursor,
+        utils::{doc_comment_text, init_cursor, reserved_lookup_in, reserved_lookup_with},
     };
 
     #[test]
@@ -410,6 +911,38 @@ pub mod tests {
         assert!(matches!(reserved_lookup("do"), TokenType::DO));
     }
 
+    #[test]
+    pub fn keyword_lookup_dialects() {
+        assert_eq!(
+            reserved_lookup_in("real", Dialect::Legacy),
+            TokenType::DOUBLE
+        );
+        assert_eq!(reserved_lookup_in("cin", Dialect::Legacy), TokenType::STDIN);
+        assert_eq!(
+            reserved_lookup_in("until", Dialect::Legacy),
+            TokenType::UNTIL
+        );
+        assert_eq!(
+            reserved_lookup_in("integer", Dialect::Legacy),
+            TokenType::ID
+        );
+        assert_eq!(
+            reserved_lookup_in("float", Dialect::CLike),
+            TokenType::DOUBLE
+        );
+        assert_eq!(
+            reserved_lookup_in("print", Dialect::CLike),
+            TokenType::STDOUT
+        );
+        assert_eq!(reserved_lookup_in("and", Dialect::CLike), TokenType::AND);
+        assert_eq!(reserved_lookup_in("int", Dialect::Vanilla), TokenType::ID);
+        let config = LexerConfig::new(Dialect::Legacy);
+        assert_eq!(reserved_lookup_with("then", &config), TokenType::THEN);
+        assert_eq!(reserved_lookup_with("stdout", &config), TokenType::ID);
+        assert_eq!("c-like".parse::<Dialect>(), Ok(Dialect::CLike));
+        assert!("pascal".parse::<Dialect>().is_err());
+    }
+
     #[test]
     pub fn keyword_lookup_id() {
         assert!(matches!(reserved_lookup("_main"), TokenType::ID));
@@ -432,19 +965,63 @@ pub mod tests {
             Token {
                 lexemme: "".to_string(), // no se debe guardar esta info
                 token_type: TokenType::EOF,
-                start: Cursor { col: 2, lin: 2 },
-                end: Cursor { col: 2, lin: 2 }
+                // el '\r' solo también es un salto de línea
+                start: Cursor { col: 2, lin: 3 },
+                end: Cursor { col: 2, lin: 3 }
             }
         )
     }
 
+    #[test]
+    pub fn get_token_ascii_fast_path() {
+        let mut cursor = init_cursor();
+        let (token, rest) = get_token(" \t\r\n  \r abc_12+", &mut cursor);
+        let token = token.unwrap();
+        assert_eq!(token.lexemme, "abc_12");
+        assert_eq!(token.start, Cursor { col: 2, lin: 3 });
+        assert_eq!(token.end, Cursor { col: 8, lin: 3 });
+        assert_eq!(rest, "+");
+        let (token, rest) = get_token("007.5;", &mut init_cursor());
+        assert_eq!(token.unwrap().token_type, TokenType::FLOAT);
+        assert_eq!(rest, ";");
+        let (token, rest) = get_token("/// año\r\n", &mut init_cursor());
+        let token = token.unwrap();
+        assert_eq!(token.token_type, TokenType::DOC_COMMENT);
+        assert_eq!(token.end, Cursor { col: 8, lin: 1 });
+        assert_eq!(rest, "\r\n");
+        // con identificadores Unicode el identificador sigue después de la parte ASCII
+        let options = LexerOptions {
+            unicode_identifiers: true,
+            ..Default::default()
+        };
+        let (token, _) = get_token_with("abcñ", &mut init_cursor(), &options, None);
+        assert_eq!(token.unwrap().lexemme, "abcñ");
+        let (token, rest) = get_token("abcñ", &mut init_cursor());
+        assert_eq!(token.unwrap().lexemme, "abc");
+        assert_eq!(rest, "ñ");
+    }
+
+    #[test]
+    pub fn get_token_line_endings() {
+        for text in ["\nx", "\r\nx", "\rx"] {
+            let token = get_token(text, &mut init_cursor()).0.unwrap();
+            assert_eq!(token.start, Cursor { col: 1, lin: 2 });
+        }
+        let (token, rest) = get_token("// a\r\nx", &mut init_cursor());
+        assert_eq!(token.unwrap().lexemme, "// a");
+        assert_eq!(rest, "\r\nx");
+        let mut cursor = init_cursor();
+        let token = get_token("/* a\r\n\rb */x", &mut cursor).0.unwrap();
+        assert_eq!(token.end, Cursor { col: 5, lin: 3 });
+    }
+
     #[test]
     pub fn get_token_float() {
-        let mut text3 = String::from("1289.23");
-        let mut text4 = String::from("1289.");
-        let mut text5 = String::from("1289");
+        let text3 = String::from("1289.23");
+        let text4 = String::from("1289.");
+        let text5 = String::from("1289");
         assert_eq!(
-            get_token(&mut text3, &mut init_cursor()).0.unwrap(),
+            get_token(&text3, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: text3,
                 token_type: TokenType::FLOAT,
@@ -452,9 +1029,9 @@ pub mod tests {
                 end: Cursor { col: 8, lin: 1 }
             }
         );
-        assert!(get_token(&mut text4, &mut init_cursor()).0.is_err());
+        assert!(get_token(&text4, &mut init_cursor()).0.is_err());
         assert_ne!(
-            get_token(&mut text5, &mut init_cursor()).0.unwrap(),
+            get_token(&text5, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: text5,
                 token_type: TokenType::FLOAT,
@@ -462,9 +1039,9 @@ pub mod tests {
                 end: Cursor { col: 6, lin: 1 }
             }
         );
-        let mut text = String::from("34.34.34.34");
+        let text = String::from("34.34.34.34");
         assert_ne!(
-            get_token(&mut text, &mut init_cursor()).0.unwrap(),
+            get_token(&text, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: text,
                 token_type: TokenType::FLOAT,
@@ -476,11 +1053,11 @@ pub mod tests {
 
     #[test]
     pub fn get_token_int() {
-        let mut text3 = String::from("1289");
-        let mut text4 = String::from("1289.");
-        let mut text5 = String::from("asd");
+        let text3 = String::from("1289");
+        let text4 = String::from("1289.");
+        let text5 = String::from("asd");
         assert_eq!(
-            get_token(&mut text3, &mut init_cursor()).0.unwrap(),
+            get_token(&text3, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: text3,
                 token_type: TokenType::INT,
@@ -488,9 +1065,9 @@ pub mod tests {
                 end: Cursor { col: 5, lin: 1 }
             }
         );
-        assert!(get_token(&mut text4, &mut init_cursor()).0.is_err());
+        assert!(get_token(&text4, &mut init_cursor()).0.is_err());
         assert_ne!(
-            get_token(&mut text5, &mut init_cursor()).0.unwrap(),
+            get_token(&text5, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: text5,
                 token_type: TokenType::INT,
@@ -502,14 +1079,14 @@ pub mod tests {
 
     #[test]
     pub fn get_token_id() {
-        let mut text0 = String::from("a");
-        let mut text1 = String::from("identificador");
-        let mut text2 = String::from("_hola");
-        let mut text3 = String::from("_var23");
-        let mut text4 = String::from("_12var");
-        let mut text5 = String::from("123");
+        let text0 = String::from("a");
+        let text1 = String::from("identificador");
+        let text2 = String::from("_hola");
+        let text3 = String::from("_var23");
+        let text4 = String::from("_12var");
+        let text5 = String::from("123");
         assert_eq!(
-            get_token(&mut text0, &mut init_cursor()).0.unwrap(),
+            get_token(&text0, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: text0,
                 token_type: TokenType::ID,
@@ -519,7 +1096,7 @@ pub mod tests {
             }
         );
         assert_eq!(
-            get_token(&mut text1, &mut init_cursor()).0.unwrap(),
+            get_token(&text1, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: text1,
                 token_type: TokenType::ID,
@@ -528,7 +1105,7 @@ pub mod tests {
             }
         );
         assert_eq!(
-            get_token(&mut text2, &mut init_cursor()).0.unwrap(),
+            get_token(&text2, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: text2,
                 token_type: TokenType::ID,
@@ -537,7 +1114,7 @@ pub mod tests {
             }
         );
         assert_eq!(
-            get_token(&mut text3, &mut init_cursor()).0.unwrap(),
+            get_token(&text3, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: text3,
                 token_type: TokenType::ID,
@@ -546,7 +1123,7 @@ pub mod tests {
             }
         );
         assert_eq!(
-            get_token(&mut text4, &mut init_cursor()).0.unwrap(),
+            get_token(&text4, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: text4,
                 token_type: TokenType::ID,
@@ -555,7 +1132,7 @@ pub mod tests {
             }
         );
         assert_ne!(
-            get_token(&mut text5, &mut init_cursor()).0.unwrap(),
+            get_token(&text5, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: text5,
                 token_type: TokenType::ID,
@@ -567,18 +1144,18 @@ pub mod tests {
 
     #[test]
     pub fn get_token_comments() {
-        let mut text1 = String::from("//");
-        let mut text2 = String::from("//\n");
-        let mut text3 = String::from("//Hola\n");
-        let mut text4 = String::from("/ / Hola");
-        let mut text5 = String::from("/**/");
-        let mut text6 = String::from("/* asd asd asd 123 1_ */");
-        let mut text7 = String::from("/**adasd/");
-        let mut text8 = String::from("/*/");
-        let mut text9 = String::from("/*");
-        let mut text10 = String::from("/**");
-        assert_eq!(
-            get_token(&mut text1, &mut init_cursor()).0.unwrap(),
+        let text1 = String::from("//");
+        let text2 = String::from("//\n");
+        let text3 = String::from("//Hola\n");
+        let text4 = String::from("/ / Hola");
+        let text5 = String::from("/**/");
+        let text6 = String::from("/* asd asd asd 123 1_ */");
+        let text7 = String::from("/**adasd/");
+        let text8 = String::from("/*/");
+        let text9 = String::from("/*");
+        let text10 = String::from("/**");
+        assert_eq!(
+            get_token(&text1, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: text1,
                 token_type: TokenType::INLINE_COMMENT,
@@ -587,7 +1164,7 @@ pub mod tests {
             }
         );
         assert_eq!(
-            get_token(&mut text2, &mut init_cursor()).0.unwrap(),
+            get_token(&text2, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: "//".to_string(),
                 token_type: TokenType::INLINE_COMMENT,
@@ -596,7 +1173,7 @@ pub mod tests {
             }
         );
         assert_eq!(
-            get_token(&mut text3, &mut init_cursor()).0.unwrap(),
+            get_token(&text3, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: "//Hola".to_string(),
                 token_type: TokenType::INLINE_COMMENT,
@@ -605,7 +1182,7 @@ pub mod tests {
             }
         );
         assert_ne!(
-            get_token(&mut text4, &mut init_cursor()).0.unwrap(),
+            get_token(&text4, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: text4,
                 token_type: TokenType::INLINE_COMMENT,
@@ -614,7 +1191,7 @@ pub mod tests {
             }
         );
         assert_eq!(
-            get_token(&mut text5, &mut init_cursor()).0.unwrap(),
+            get_token(&text5, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: text5,
                 token_type: TokenType::BLOCK_COMMENT,
@@ -623,7 +1200,7 @@ pub mod tests {
             }
         );
         assert_eq!(
-            get_token(&mut text6, &mut init_cursor()).0.unwrap(),
+            get_token(&text6, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: text6,
                 token_type: TokenType::BLOCK_COMMENT,
@@ -631,17 +1208,17 @@ pub mod tests {
                 end: Cursor { col: 25, lin: 1 }
             }
         );
-        assert!(get_token(&mut text7, &mut init_cursor()).0.is_err());
-        assert!(get_token(&mut text8, &mut init_cursor()).0.is_err());
-        assert!(get_token(&mut text9, &mut init_cursor()).0.is_err());
-        assert!(get_token(&mut text10, &mut init_cursor()).0.is_err());
+        assert!(get_token(&text7, &mut init_cursor()).0.is_err());
+        assert!(get_token(&text8, &mut init_cursor()).0.is_err());
+        assert!(get_token(&text9, &mut init_cursor()).0.is_err());
+        assert!(get_token(&text10, &mut init_cursor()).0.is_err());
     }
 
     #[test]
     pub fn get_token_operators() {
-        let mut operator = String::from("+");
+        let operator = String::from("+");
         assert_eq!(
-            get_token(&mut operator, &mut init_cursor()).0.unwrap(),
+            get_token(&operator, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: operator,
                 token_type: TokenType::SUM,
@@ -649,9 +1226,9 @@ pub mod tests {
                 end: Cursor { col: 2, lin: 1 }
             }
         );
-        let mut operator = String::from("-");
+        let operator = String::from("-");
         assert_eq!(
-            get_token(&mut operator, &mut init_cursor()).0.unwrap(),
+            get_token(&operator, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: operator,
                 token_type: TokenType::MIN,
@@ -659,9 +1236,9 @@ pub mod tests {
                 end: Cursor { col: 2, lin: 1 }
             }
         );
-        let mut operator = String::from("*");
+        let operator = String::from("*");
         assert_eq!(
-            get_token(&mut operator, &mut init_cursor()).0.unwrap(),
+            get_token(&operator, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: operator,
                 token_type: TokenType::TIMES,
@@ -669,9 +1246,9 @@ pub mod tests {
                 end: Cursor { col: 2, lin: 1 }
             }
         );
-        let mut operator = String::from("/");
+        let operator = String::from("/");
         assert_eq!(
-            get_token(&mut operator, &mut init_cursor()).0.unwrap(),
+            get_token(&operator, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: operator,
                 token_type: TokenType::DIV,
@@ -679,9 +1256,9 @@ pub mod tests {
                 end: Cursor { col: 2, lin: 1 }
             }
         );
-        let mut operator = String::from("%");
+        let operator = String::from("%");
         assert_eq!(
-            get_token(&mut operator, &mut init_cursor()).0.unwrap(),
+            get_token(&operator, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: operator,
                 token_type: TokenType::MODULUS,
@@ -689,9 +1266,9 @@ pub mod tests {
                 end: Cursor { col: 2, lin: 1 }
             }
         );
-        let mut operator = String::from("^");
+        let operator = String::from("^");
         assert_eq!(
-            get_token(&mut operator, &mut init_cursor()).0.unwrap(),
+            get_token(&operator, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: operator,
                 token_type: TokenType::POWER,
@@ -700,9 +1277,9 @@ pub mod tests {
                 end: Cursor { col: 2, lin: 1 }
             }
         );
-        let mut operator = String::from("++");
+        let operator = String::from("++");
         assert_eq!(
-            get_token(&mut operator, &mut init_cursor()).0.unwrap(),
+            get_token(&operator, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: operator,
                 token_type: TokenType::INC,
@@ -711,9 +1288,9 @@ pub mod tests {
                 end: Cursor { col: 3, lin: 1 }
             }
         );
-        let mut operator = String::from("--");
+        let operator = String::from("--");
         assert_eq!(
-            get_token(&mut operator, &mut init_cursor()).0.unwrap(),
+            get_token(&operator, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: operator,
                 token_type: TokenType::DEC,
@@ -725,9 +1302,9 @@ pub mod tests {
 
     #[test]
     pub fn get_token_symbols() {
-        let mut symbol = String::from(",");
+        let symbol = String::from(",");
         assert_eq!(
-            get_token(&mut symbol, &mut init_cursor()).0.unwrap(),
+            get_token(&symbol, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: symbol,
                 token_type: TokenType::COMMA,
@@ -736,9 +1313,9 @@ pub mod tests {
                 end: Cursor { col: 2, lin: 1 }
             }
         );
-        let mut symbol = String::from(";");
+        let symbol = String::from(";");
         assert_eq!(
-            get_token(&mut symbol, &mut init_cursor()).0.unwrap(),
+            get_token(&symbol, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: symbol,
                 token_type: TokenType::SCOL,
@@ -746,9 +1323,9 @@ pub mod tests {
                 end: Cursor { col: 2, lin: 1 }
             }
         );
-        let mut symbol = String::from("(");
+        let symbol = String::from("(");
         assert_eq!(
-            get_token(&mut symbol, &mut init_cursor()).0.unwrap(),
+            get_token(&symbol, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: symbol,
                 token_type: TokenType::LPAR,
@@ -756,9 +1333,9 @@ pub mod tests {
                 end: Cursor { col: 2, lin: 1 }
             }
         );
-        let mut symbol = String::from(")");
+        let symbol = String::from(")");
         assert_eq!(
-            get_token(&mut symbol, &mut init_cursor()).0.unwrap(),
+            get_token(&symbol, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: symbol,
                 token_type: TokenType::RPAR,
@@ -766,9 +1343,9 @@ pub mod tests {
                 end: Cursor { col: 2, lin: 1 }
             }
         );
-        let mut symbol = String::from("{");
+        let symbol = String::from("{");
         assert_eq!(
-            get_token(&mut symbol, &mut init_cursor()).0.unwrap(),
+            get_token(&symbol, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: symbol,
                 token_type: TokenType::LBRA,
@@ -776,9 +1353,9 @@ pub mod tests {
                 end: Cursor { col: 2, lin: 1 }
             }
         );
-        let mut symbol = String::from("}");
+        let symbol = String::from("}");
         assert_eq!(
-            get_token(&mut symbol, &mut init_cursor()).0.unwrap(),
+            get_token(&symbol, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: symbol,
                 token_type: TokenType::RBRA,
@@ -791,9 +1368,9 @@ pub mod tests {
 
     #[test]
     pub fn get_token_rel_op() {
-        let mut symbol = String::from("!");
+        let symbol = String::from("!");
         assert_eq!(
-            get_token(&mut symbol, &mut init_cursor()).0.unwrap(),
+            get_token(&symbol, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: symbol,
                 token_type: TokenType::NEG,
@@ -801,9 +1378,9 @@ pub mod tests {
                 end: Cursor { col: 2, lin: 1 }
             }
         );
-        let mut symbol = String::from("!=");
+        let symbol = String::from("!=");
         assert_eq!(
-            get_token(&mut symbol, &mut init_cursor()).0.unwrap(),
+            get_token(&symbol, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: symbol,
                 token_type: TokenType::NE,
@@ -811,9 +1388,9 @@ pub mod tests {
                 end: Cursor { col: 3, lin: 1 }
             }
         );
-        let mut symbol = String::from("==");
+        let symbol = String::from("==");
         assert_eq!(
-            get_token(&mut symbol, &mut init_cursor()).0.unwrap(),
+            get_token(&symbol, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: symbol,
                 token_type: TokenType::EQ,
@@ -822,9 +1399,9 @@ pub mod tests {
                 end: Cursor { col: 3, lin: 1 }
             }
         );
-        let mut symbol = String::from("<");
+        let symbol = String::from("<");
         assert_eq!(
-            get_token(&mut symbol, &mut init_cursor()).0.unwrap(),
+            get_token(&symbol, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: symbol,
                 token_type: TokenType::LT,
@@ -832,9 +1409,9 @@ pub mod tests {
                 end: Cursor { col: 2, lin: 1 }
             }
         );
-        let mut symbol = String::from("<=");
+        let symbol = String::from("<=");
         assert_eq!(
-            get_token(&mut symbol, &mut init_cursor()).0.unwrap(),
+            get_token(&symbol, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: symbol,
                 token_type: TokenType::LE,
@@ -842,9 +1419,9 @@ pub mod tests {
                 end: Cursor { col: 3, lin: 1 }
             }
         );
-        let mut symbol = String::from(">");
+        let symbol = String::from(">");
         assert_eq!(
-            get_token(&mut symbol, &mut init_cursor()).0.unwrap(),
+            get_token(&symbol, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: symbol,
                 token_type: TokenType::GT,
@@ -852,9 +1429,9 @@ pub mod tests {
                 end: Cursor { col: 2, lin: 1 }
             }
         );
-        let mut symbol = String::from(">=");
+        let symbol = String::from(">=");
         assert_eq!(
-            get_token(&mut symbol, &mut init_cursor()).0.unwrap(),
+            get_token(&symbol, &mut init_cursor()).0.unwrap(),
             Token {
                 lexemme: symbol,
                 token_type: TokenType::GE,
@@ -864,4 +1441,232 @@ pub mod tests {
             }
         );
     }
+
+    #[test]
+    pub fn get_token_signed_numbers() {
+        let options = LexerOptions {
+            signed_numbers: true,
+            ..Default::default()
+        };
+        let text = String::from("-12.5");
+        assert_eq!(
+            get_token_with(
+                &text,
+                &mut init_cursor(),
+                &options,
+                Some(&TokenType::ASSIGN)
+            )
+            .0
+            .unwrap(),
+            Token {
+                lexemme: text,
+                token_type: TokenType::FLOAT,
+                start: init_cursor(),
+                end: Cursor { col: 6, lin: 1 }
+            }
+        );
+        let text = String::from("+3");
+        assert_eq!(
+            get_token_with(&text, &mut init_cursor(), &options, None)
+                .0
+                .unwrap()
+                .token_type,
+            TokenType::INT
+        );
+        assert_eq!(
+            get_token_with(&text, &mut init_cursor(), &options, Some(&TokenType::INT))
+                .0
+                .unwrap()
+                .token_type,
+            TokenType::SUM
+        );
+        assert_eq!(
+            get_token(&text, &mut init_cursor()).0.unwrap().token_type,
+            TokenType::SUM
+        );
+        let text = String::from("--3");
+        assert_eq!(
+            get_token_with(&text, &mut init_cursor(), &options, None)
+                .0
+                .unwrap()
+                .token_type,
+            TokenType::DEC
+        );
+    }
+
+    #[test]
+    pub fn get_token_nested_comments() {
+        let options = LexerOptions {
+            nested_comments: true,
+            ..Default::default()
+        };
+        let text = String::from("/* a /* b */ c */");
+        assert_eq!(
+            get_token_with(&text, &mut init_cursor(), &options, None)
+                .0
+                .unwrap(),
+            Token {
+                lexemme: text.clone(),
+                token_type: TokenType::BLOCK_COMMENT,
+                start: init_cursor(),
+                end: Cursor { col: 18, lin: 1 }
+            }
+        );
+        let (_, rest) = get_token(&text, &mut init_cursor());
+        assert_eq!(rest, " c */");
+        let text = String::from("/* a\n  /* b */ /*/ */\n*/ x");
+        let (result, rest) = get_token_with(&text, &mut init_cursor(), &options, None);
+        assert_eq!(result.unwrap().end, Cursor { col: 3, lin: 3 });
+        assert_eq!(rest, " x");
+        let text = String::from("/* a\n  /* b /* c */");
+        let error = get_token_with(&text, &mut init_cursor(), &options, None)
+            .0
+            .unwrap_err();
+        assert_eq!(error.start, Cursor { col: 3, lin: 2 });
+        assert_eq!(error.end, Cursor { col: 15, lin: 2 });
+    }
+
+    #[test]
+    pub fn get_token_doc_comments() {
+        let text = String::from("/** Suma dos\n * numeros\n **/");
+        let token = get_token(&text, &mut init_cursor()).0.unwrap();
+        assert_eq!(token.token_type, TokenType::DOC_COMMENT);
+        assert_eq!(doc_comment_text(&token.lexemme), "Suma dos\nnumeros");
+        let text = String::from("/// Punto de entrada\nmain");
+        let (token, rest) = get_token(&text, &mut init_cursor());
+        let token = token.unwrap();
+        assert_eq!(token.token_type, TokenType::DOC_COMMENT);
+        assert_eq!(doc_comment_text(&token.lexemme), "Punto de entrada");
+        assert_eq!(rest, "\nmain");
+        for text in [
+            "/**/",
+            "/*** separador ***/",
+            "//// nada",
+            "/* comentario */",
+        ] {
+            assert_ne!(
+                get_token(text, &mut init_cursor()).0.unwrap().token_type,
+                TokenType::DOC_COMMENT
+            );
+        }
+    }
+
+    #[test]
+    pub fn get_token_none() {
+        let options = LexerOptions {
+            none_tokens: true,
+            ..Default::default()
+        };
+        let text = String::from("?#@ a");
+        let (token, rest) = get_token_with(&text, &mut init_cursor(), &options, None);
+        assert_eq!(
+            token.unwrap(),
+            Token {
+                lexemme: "?#@".to_string(),
+                token_type: TokenType::NONE,
+                start: init_cursor(),
+                end: Cursor { col: 4, lin: 1 }
+            }
+        );
+        assert_eq!(rest, " a");
+        let (_, rest) = get_token_with("&&(", &mut init_cursor(), &options, None);
+        assert_eq!(rest, "(");
+        assert!(get_token("?#@", &mut init_cursor()).0.is_err());
+    }
+
+    #[test]
+    pub fn get_token_c_operators() {
+        let options = LexerOptions::c_like();
+        let text = String::from("&&");
+        assert_eq!(
+            get_token_with(&text, &mut init_cursor(), &options, None)
+                .0
+                .unwrap(),
+            Token {
+                lexemme: text,
+                token_type: TokenType::AND,
+                start: init_cursor(),
+                end: Cursor { col: 3, lin: 1 }
+            }
+        );
+        let text = String::from("||");
+        assert_eq!(
+            get_token_with(&text, &mut init_cursor(), &options, None)
+                .0
+                .unwrap()
+                .token_type,
+            TokenType::OR
+        );
+        let (result, rest) = get_token_with("&|", &mut init_cursor(), &options, None);
+        let error = result.unwrap_err();
+        assert_eq!(error.end, Cursor { col: 2, lin: 1 });
+        assert_eq!(rest, "|");
+        assert!(get_token_with("|", &mut init_cursor(), &options, None)
+            .0
+            .is_err());
+        assert!(get_token("&&", &mut init_cursor()).0.is_err());
+        let options = LexerOptions {
+            none_tokens: true,
+            ..LexerOptions::c_like()
+        };
+        let (result, rest) = get_token_with("&? a", &mut init_cursor(), &options, None);
+        assert_eq!(result.unwrap().lexemme, "&?");
+        assert_eq!(rest, " a");
+    }
+
+    #[test]
+    pub fn get_token_compound_assignment() {
+        for (text, token_type) in [
+            ("+=", TokenType::SUM_ASSIGN),
+            ("-=", TokenType::MIN_ASSIGN),
+            ("*=", TokenType::TIMES_ASSIGN),
+            ("/=", TokenType::DIV_ASSIGN),
+            ("%=", TokenType::MODULUS_ASSIGN),
+            ("^=", TokenType::POWER_ASSIGN),
+        ] {
+            assert_eq!(
+                get_token(text, &mut init_cursor()).0.unwrap(),
+                Token {
+                    lexemme: text.to_string(),
+                    token_type,
+                    start: init_cursor(),
+                    end: Cursor { col: 3, lin: 1 }
+                }
+            );
+        }
+        let (token, rest) = get_token("++=", &mut init_cursor());
+        assert_eq!(token.unwrap().token_type, TokenType::INC);
+        assert_eq!(rest, "=");
+        let (token, rest) = get_token("*==", &mut init_cursor());
+        assert_eq!(token.unwrap().token_type, TokenType::TIMES_ASSIGN);
+        assert_eq!(rest, "=");
+        let (token, rest) = get_token("^2", &mut init_cursor());
+        assert_eq!(token.unwrap().token_type, TokenType::POWER);
+        assert_eq!(rest, "2");
+    }
+
+    #[test]
+    pub fn get_token_punctuation() {
+        for (text, token_type) in [
+            (":", TokenType::COLON),
+            ("[", TokenType::LBRACK),
+            ("]", TokenType::RBRACK),
+            (".", TokenType::DOT),
+        ] {
+            assert_eq!(
+                get_token(text, &mut init_cursor()).0.unwrap(),
+                Token {
+                    lexemme: text.to_string(),
+                    token_type,
+                    start: init_cursor(),
+                    end: Cursor { col: 2, lin: 1 }
+                }
+            );
+        }
+        let (result, rest) = get_token(":= 1", &mut init_cursor());
+        let error = result.unwrap_err();
+        assert_eq!(error.lexemme, ":=");
+        assert_eq!(error.end, Cursor { col: 3, lin: 1 });
+        assert_eq!(rest, " 1");
+    }
 }
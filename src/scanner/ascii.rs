@@ -0,0 +1,149 @@
+//! Camino rápido para texto ASCII: clasifica bytes con una tabla, salta espacios en bloque y lee
+//! identificadores, enteros y comentarios de línea sin decodificar carácter por carácter. Cualquier caso que no sea
+//! trivial (flotantes, Unicode) se deja a la máquina de estados de `get_token_ref`.
+
+use std::borrow::Cow;
+
+use crate::{
+    data::{Cursor, TokenRef, TokenType},
+    options::LexerOptions,
+    utils::is_doc_comment,
+};
+
+const OTHER: u8 = 0;
+const WHITESPACE: u8 = 1;
+const DIGIT: u8 = 2;
+const LETTER: u8 = 3;
+
+/// Clase de cada byte ASCII; los bytes >= 0x80 son `OTHER`
+static CLASSES: [u8; 256] = {
+    let mut classes = [OTHER; 256];
+    let mut b = 0;
+    while b < 128 {
+        classes[b] = match b as u8 {
+            b' ' | b'\t' | b'\r' | b'\n' => WHITESPACE,
+            b'0'..=b'9' => DIGIT,
+            b'a'..=b'z' | b'A'..=b'Z' | b'_' => LETTER,
+            _ => OTHER,
+        };
+        b += 1;
+    }
+    classes
+};
+
+fn class(b: u8) -> u8 {
+    CLASSES[b as usize]
+}
+
+/// Salta los espacios y saltos de línea del inicio de `text`, moviendo el cursor igual que el
+/// estado `START`
+pub fn skip_whitespace<'a>(text: &'a str, cursor: &mut Cursor) -> &'a str {
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() && class(bytes[i]) == WHITESPACE {
+        match bytes[i] {
+            b'\n' => {
+                cursor.lin += 1;
+                cursor.col = 1;
+            }
+            // en "\r\n" el salto lo hace el '\n'
+            b'\r' if bytes.get(i + 1) == Some(&b'\n') => {}
+            b'\r' => {
+                cursor.lin += 1;
+                cursor.col = 1;
+            }
+            _ => cursor.col += 1,
+        }
+        i += 1;
+    }
+    &text[i..]
+}
+
+/// Lee un identificador o entero ASCII al inicio de `text`. Regresa `None` si el token necesita
+/// la máquina de estados (un número con '.', o un identificador seguido de Unicode cuando se
+/// permiten identificadores Unicode).
+pub fn scan_word<'a>(
+    text: &'a str,
+    cursor: &mut Cursor,
+    options: &LexerOptions,
+) -> Option<(TokenRef<'a>, &'a str)> {
+    let bytes = text.as_bytes();
+    let first = class(*bytes.first()?);
+    let token_type = match first {
+        LETTER => TokenType::ID,
+        DIGIT => TokenType::INT,
+        _ => return None,
+    };
+    let mut end = 1;
+    if first == LETTER {
+        while end < bytes.len() && matches!(class(bytes[end]), LETTER | DIGIT) {
+            end += 1;
+        }
+        if options.unicode_identifiers && bytes.get(end).is_some_and(|b| !b.is_ascii()) {
+            return None;
+        }
+    } else {
+        while end < bytes.len() && class(bytes[end]) == DIGIT {
+            end += 1;
+        }
+        if bytes.get(end) == Some(&b'.') {
+            return None;
+        }
+    }
+    let lexemme = &text[..end];
+    let start = cursor.clone();
+    cursor.col += end as i32;
+    let token_type = match token_type {
+        TokenType::ID => identifier_type(lexemme, options),
+        token_type => token_type,
+    };
+    Some((
+        TokenRef {
+            token_type,
+            lexemme: Cow::Borrowed(lexemme),
+            start,
+            end: cursor.clone(),
+        },
+        &text[end..],
+    ))
+}
+
+/// Lee un comentario `//` hasta el salto de línea sin incluirlo, como el estado `LINE_COM`
+pub fn scan_line_comment<'a>(
+    text: &'a str,
+    cursor: &mut Cursor,
+) -> Option<(TokenRef<'a>, &'a str)> {
+    if !text.starts_with("//") {
+        return None;
+    }
+    let end = text.find(['\r', '\n']).unwrap_or(text.len());
+    let lexemme = &text[..end];
+    let start = cursor.clone();
+    cursor.col += lexemme.chars().count() as i32;
+    let token_type = if is_doc_comment(lexemme) {
+        TokenType::DOC_COMMENT
+    } else {
+        TokenType::INLINE_COMMENT
+    };
+    Some((
+        TokenRef {
+            token_type,
+            lexemme: Cow::Borrowed(lexemme),
+            start,
+            end: cursor.clone(),
+        },
+        &text[end..],
+    ))
+}
+
+/// Palabra reservada o `ID` para un identificador ya normalizado
+pub fn identifier_type(lexemme: &str, options: &LexerOptions) -> TokenType {
+    match options.config.keyword(lexemme) {
+        Some(keyword) => keyword.clone(),
+        None if options.case_insensitive_keywords => options
+            .config
+            .keyword_ignore_case(lexemme)
+            .map_or(TokenType::ID, |(_, keyword)| keyword.clone()),
+        None => TokenType::ID,
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{
+    codes,
+    data::{Error, Severity, Suggestion, Token, TokenType},
+};
+
+/// Revisa que los paréntesis y las llaves de `tokens` (la salida de `tokenize`) estén balanceados.
+/// Reporta cada apertura sin cerrar y cada cierre sobrante con la posición de su pareja probable y
+/// una corrección: agregar el cierre que falta, cambiar un cierre por el correcto o quitarlo.
+pub fn check_brackets(tokens: &[Token]) -> Vec<Error> {
+    let mut errors = Vec::new();
+    // índices de las aperturas sin cerrar
+    let mut open: Vec<usize> = Vec::new();
+    for (index, token) in tokens.iter().enumerate() {
+        match token.token_type {
+            TokenType::LPAR | TokenType::LBRA => open.push(index),
+            TokenType::RPAR | TokenType::RBRA => {
+                let opener = opener_of(&token.token_type);
+                match open.iter().rposition(|o| tokens[*o].token_type == opener) {
+                    // las aperturas que quedan dentro del par se debieron cerrar antes
+                    Some(depth) => {
+                        for unclosed_index in open.drain(depth + 1..) {
+                            errors.push(unclosed(tokens, unclosed_index, Some(index)));
+                        }
+                        open.pop();
+                    }
+                    // en la misma línea es más probable que se haya escrito mal el cierre
+                    None => match open.last() {
+                        Some(last) if tokens[*last].start.lin == token.start.lin => {
+                            errors.push(mismatched(&tokens[*last], token));
+                            open.pop();
+                        }
+                        last => errors.push(stray(token, last.map(|last| &tokens[*last]))),
+                    },
+                }
+            }
+            _ => {}
+        }
+    }
+    for index in open {
+        errors.push(unclosed(tokens, index, None));
+    }
+    errors.sort_by_key(|error| (error.start.lin, error.start.col));
+    errors
+}
+
+fn opener_of(closer: &TokenType) -> TokenType {
+    match closer {
+        TokenType::RPAR => TokenType::LPAR,
+        _ => TokenType::LBRA,
+    }
+}
+
+fn closer_of(opener: &TokenType) -> &'static str {
+    match opener {
+        TokenType::LPAR => ")",
+        _ => "}",
+    }
+}
+
+/// Apertura `tokens[index]` sin cerrar antes del cierre `tokens[limit]` o del final del archivo
+fn unclosed(tokens: &[Token], index: usize, limit: Option<usize>) -> Error {
+    let token = &tokens[index];
+    let closer = closer_of(&token.token_type);
+    let inside = &tokens[index + 1..limit.unwrap_or(tokens.len())];
+    // un paréntesis casi siempre se cierra en su línea, antes de un bloque o del fin de instrucción
+    let position = if token.token_type == TokenType::LPAR {
+        let line = inside.iter().take_while(|t| t.start.lin == token.start.lin);
+        let mut ends = line.clone().filter(|t| {
+            matches!(
+                t.token_type,
+                TokenType::LBRA | TokenType::SCOL | TokenType::THEN | TokenType::DO
+            )
+        });
+        match ends.next() {
+            Some(end) => end.start.clone(),
+            None => line.last().unwrap_or(token).end.clone(),
+        }
+    } else {
+        match limit {
+            Some(limit) => tokens[limit].start.clone(),
+            None => inside.last().unwrap_or(token).end.clone(),
+        }
+    };
+    let message = match limit {
+        Some(limit) => format!(
+            "El '{}' no se cierra antes del '{}' (línea {}, columna {})",
+            token.lexemme, tokens[limit].lexemme, tokens[limit].start.lin, tokens[limit].start.col
+        ),
+        None => format!("El '{}' no se cierra", token.lexemme),
+    };
+    error(
+        token,
+        message,
+        Suggestion {
+            message: format!("Agrega '{}'", closer),
+            replacement: closer.to_string(),
+            start: position.clone(),
+            end: position,
+        },
+    )
+}
+
+/// Cierre que no corresponde con la apertura de la misma línea
+fn mismatched(opener: &Token, token: &Token) -> Error {
+    let closer = closer_of(&opener.token_type);
+    error(
+        token,
+        format!(
+            "El '{}' cierra el '{}' (línea {}, columna {})",
+            token.lexemme, opener.lexemme, opener.start.lin, opener.start.col
+        ),
+        Suggestion {
+            message: format!("Escribe '{}'", closer),
+            replacement: closer.to_string(),
+            start: token.start.clone(),
+            end: token.end.clone(),
+        },
+    )
+}
+
+/// Cierre sin apertura; `last` es la última apertura sin cerrar, de otro tipo
+fn stray(token: &Token, last: Option<&Token>) -> Error {
+    let opener = if token.token_type == TokenType::RPAR {
+        "("
+    } else {
+        "{"
+    };
+    let mut message = format!(
+        "El '{}' no tiene un '{}' que lo abra",
+        token.lexemme, opener
+    );
+    if let Some(last) = last {
+        message.push_str(&format!(
+            "; el último abierto es el '{}' (línea {}, columna {})",
+            last.lexemme, last.start.lin, last.start.col
+        ));
+    }
+    error(
+        token,
+        message,
+        Suggestion {
+            message: format!("Quita el '{}'", token.lexemme),
+            replacement: String::new(),
+            start: token.start.clone(),
+            end: token.end.clone(),
+        },
+    )
+}
+
+fn error(token: &Token, message: String, suggestion: Suggestion) -> Error {
+    Error {
+        start: token.start.clone(),
+        end: token.end.clone(),
+        message,
+        lexemme: token.lexemme.clone(),
+        suggestion: Some(suggestion),
+        severity: Severity::Error,
+        code: codes::UNBALANCED_BRACKET.to_string(),
+    }
+}
@@ -0,0 +1,44 @@
+//! Códigos de los diagnósticos. Los errores léxicos empiezan con `E` y las advertencias con `L`;
+//! se usan para identificarlos en la salida y para suprimirlos con pragmas.
+
+/// Error inesperado del scanner
+pub const UNEXPECTED: &str = "E0000";
+/// Simbolo que no pertenece al lenguaje
+pub const UNKNOWN_SYMBOL: &str = "E0001";
+/// Número flotante sin dígitos después del punto (`2019.`)
+pub const INCOMPLETE_FLOAT: &str = "E0002";
+/// Comentario de bloque sin cerrar
+pub const UNTERMINATED_COMMENT: &str = "E0003";
+/// Bytes que no son UTF-8 válido
+pub const INVALID_UTF8: &str = "E0004";
+/// Token distinto al que se esperaba (`TokenStream::expect`)
+pub const UNEXPECTED_TOKEN: &str = "E0005";
+/// Paréntesis o llave sin pareja
+pub const UNBALANCED_BRACKET: &str = "E0006";
+/// Número mal formado: un `.` seguido de un dígito que no es parte de un flotante (`34.34.34`)
+pub const MALFORMED_NUMBER: &str = "E0007";
+
+/// Identificador más largo de lo permitido
+pub const LONG_IDENTIFIER: &str = "L0001";
+/// Palabra reservada con mayúsculas distintas a las de la tabla (`WHILE`)
+pub const KEYWORD_CASE: &str = "L0002";
+/// Indentación que mezcla tabuladores y espacios
+pub const MIXED_INDENTATION: &str = "L0003";
+/// Espacios al final de una línea
+pub const TRAILING_WHITESPACE: &str = "L0004";
+/// Número con ceros a la izquierda (`007`)
+pub const LEADING_ZEROS: &str = "L0005";
+/// Comentario que parece código comentado
+pub const COMMENTED_OUT_CODE: &str = "L0006";
+/// Identificador que mezcla sistemas de escritura
+pub const MIXED_SCRIPT: &str = "L0007";
+/// Identificador que se confunde visualmente con otro
+pub const CONFUSABLE_IDENTIFIER: &str = "L0008";
+/// Comentario `scanner:` que no es un pragma válido
+pub const UNKNOWN_PRAGMA: &str = "L0009";
+/// Clave desconocida o valor inválido en la directiva de cabecera
+pub const UNKNOWN_DIRECTIVE: &str = "L0010";
+/// Archivo con más de un estilo de salto de línea
+pub const MIXED_LINE_ENDINGS: &str = "L0011";
+/// Identificador que en otros dialectos es palabra reservada (`real`, `cin`)
+pub const FOREIGN_KEYWORD: &str = "L0012";
@@ -1,5 +1,7 @@
 #![allow(non_camel_case_types)]
 
+use std::borrow::Cow;
+
 use phf::phf_map;
 use serde::{Deserialize, Serialize};
 
@@ -10,11 +12,15 @@ pub enum TokenType {
     ID,
     INLINE_COMMENT,
     BLOCK_COMMENT,
+    DOC_COMMENT, // /** */ y ///
     // Palabras reservadas
     IF,
+    THEN,
     ELSE,
+    END,
     DO,
     WHILE,
+    UNTIL,
     SWITCH,
     CASE,
     INTEGER,
@@ -32,6 +38,13 @@ pub enum TokenType {
     POWER,
     INC, // ++
     DEC, // --
+    // Asignacion compuesta
+    SUM_ASSIGN,     // +=
+    MIN_ASSIGN,     // -=
+    TIMES_ASSIGN,   // *=
+    DIV_ASSIGN,     // /=
+    MODULUS_ASSIGN, // %=
+    POWER_ASSIGN,   // ^=
     // Operadores relacionales
     LT,
     LE,
@@ -49,9 +62,15 @@ pub enum TokenType {
     RPAR,
     LBRA,
     RBRA,
+    LBRACK, // [
+    RBRACK, // ]
     SCOL,
+    COLON,
+    DOT,
     // Asignacion
     ASSIGN,
+    // Definido por el usuario en `LexerConfig`
+    CUSTOM(String),
     //EOF
     EOF,
     // Ninguno
@@ -64,6 +83,7 @@ pub enum State {
     LINE_COM,
     BLOCK_COM_1,
     BLOCK_COM_2,
+    BLOCK_COM_SLASH, // '/' dentro de un comentario, posible comentario anidado
     FLOAT,
     FLOAT_DOT,
     SUB,
@@ -74,6 +94,11 @@ pub enum State {
     GT,
     NEG,
     EQ,
+    OPERATOR, // operador que puede formar una asignacion compuesta (*=, %=, ^=)
+    COLON,
+    AMP,  // '&', esperando el segundo de "&&"
+    PIPE, // '|', esperando el segundo de "||"
+    NONE,
     DONE,
 }
 
@@ -103,7 +128,11 @@ pub static SYMBOLS: phf::Map<&'static str, TokenType> = phf_map! {
     ")" => TokenType::RPAR,
     "{" => TokenType::LBRA,
     "}" => TokenType::RBRA,
+    "[" => TokenType::LBRACK,
+    "]" => TokenType::RBRACK,
     ";" => TokenType::SCOL,
+    ":" => TokenType::COLON,
+    "." => TokenType::DOT,
 };
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
@@ -114,6 +143,48 @@ pub struct Token {
     pub end: Cursor,
 }
 
+/// Token cuyo lexema se toma prestado del texto fuente; `Token` es su versión con dueño
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenRef<'src> {
+    pub token_type: TokenType,
+    pub lexemme: Cow<'src, str>,
+    pub start: Cursor,
+    pub end: Cursor,
+}
+
+impl TokenRef<'_> {
+    pub fn into_owned(self) -> Token {
+        Token {
+            token_type: self.token_type,
+            lexemme: self.lexemme.into_owned(),
+            start: self.start,
+            end: self.end,
+        }
+    }
+
+    pub fn to_token(&self) -> Token {
+        self.clone().into_owned()
+    }
+}
+
+impl<'src> From<TokenRef<'src>> for Token {
+    fn from(token: TokenRef<'src>) -> Self {
+        token.into_owned()
+    }
+}
+
+impl Token {
+    /// Vista prestada del token
+    pub fn as_token_ref(&self) -> TokenRef<'_> {
+        TokenRef {
+            token_type: self.token_type.clone(),
+            lexemme: Cow::Borrowed(&self.lexemme),
+            start: self.start.clone(),
+            end: self.end.clone(),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Cursor {
     pub col: i32,
@@ -126,4 +197,38 @@ pub struct Error {
     pub end: Cursor,
     pub message: String,
     pub lexemme: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<Suggestion>,
+    #[serde(default)]
+    pub severity: Severity,
+    /// Ver `codes`
+    #[serde(default)]
+    pub code: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Severity {
+    #[default]
+    Error,
+    Warning,
+}
+
+/// Reemplazo propuesto para corregir un error: sustituir el texto entre `start` y `end`
+/// por `replacement`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    pub message: String,
+    pub replacement: String,
+    pub start: Cursor,
+    pub end: Cursor,
+}
+
+/// Comentario de documentación ya limpio (sin `/**`, `*/`, `///` ni `*` al inicio de cada línea)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DocComment {
+    pub text: String,
+    pub start: Cursor,
+    pub end: Cursor,
+    /// Posición en la lista de tokens del token documentado, `None` si no hay ninguno después
+    pub token: Option<usize>,
 }
@@ -0,0 +1,117 @@
+use std::{fmt, str::FromStr};
+
+use phf::phf_map;
+use serde::{Deserialize, Serialize};
+
+use crate::data::{TokenType, KEYWORDS, SYMBOLS};
+
+/// Variante del lenguaje. Cada dialecto tiene su propia tabla de palabras reservadas y de símbolos.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Dialect {
+    /// El lenguaje actual (`integer`, `double`, `stdin`, `stdout`)
+    #[default]
+    Vanilla,
+    /// El de `data/tokens.cat`: `int`, `real`, `cin`, `cout`, `then`, `end`, `until`
+    Legacy,
+    /// El de `data/test.cat`: `int`, `float`, `print`, con `&&` y `||` además de `and` y `or`
+    CLike,
+}
+
+pub static LEGACY_KEYWORDS: phf::Map<&'static str, TokenType> = phf_map! {
+    "if" => TokenType::IF,
+    "then" => TokenType::THEN,
+    "else" => TokenType::ELSE,
+    "end" => TokenType::END,
+    "do" => TokenType::DO,
+    "while" => TokenType::WHILE,
+    "until" => TokenType::UNTIL,
+    "switch" => TokenType::SWITCH,
+    "case" => TokenType::CASE,
+    "int" => TokenType::INTEGER,
+    "real" => TokenType::DOUBLE,
+    "main" => TokenType::MAIN,
+    "and" => TokenType::AND,
+    "or" => TokenType::OR,
+    "cin" => TokenType::STDIN,
+    "cout" => TokenType::STDOUT
+};
+
+pub static C_LIKE_KEYWORDS: phf::Map<&'static str, TokenType> = phf_map! {
+    "if" => TokenType::IF,
+    "else" => TokenType::ELSE,
+    "do" => TokenType::DO,
+    "while" => TokenType::WHILE,
+    "switch" => TokenType::SWITCH,
+    "case" => TokenType::CASE,
+    "int" => TokenType::INTEGER,
+    "float" => TokenType::DOUBLE,
+    "main" => TokenType::MAIN,
+    "and" => TokenType::AND,
+    "or" => TokenType::OR,
+    "print" => TokenType::STDOUT
+};
+
+/// Símbolos de un solo caracter anteriores a `[`, `]`, `:` y `.`
+pub static LEGACY_SYMBOLS: phf::Map<&'static str, TokenType> = phf_map! {
+    "*" => TokenType::TIMES,
+    "%" => TokenType::MODULUS,
+    "^" => TokenType::POWER,
+    "," => TokenType::COMMA,
+    "(" => TokenType::LPAR,
+    ")" => TokenType::RPAR,
+    "{" => TokenType::LBRA,
+    "}" => TokenType::RBRA,
+    ";" => TokenType::SCOL,
+};
+
+impl Dialect {
+    pub const ALL: [Dialect; 3] = [Dialect::Vanilla, Dialect::Legacy, Dialect::CLike];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Dialect::Vanilla => "vanilla",
+            Dialect::Legacy => "legacy",
+            Dialect::CLike => "c-like",
+        }
+    }
+
+    pub fn keywords(&self) -> &'static phf::Map<&'static str, TokenType> {
+        match self {
+            Dialect::Vanilla => &KEYWORDS,
+            Dialect::Legacy => &LEGACY_KEYWORDS,
+            Dialect::CLike => &C_LIKE_KEYWORDS,
+        }
+    }
+
+    pub fn symbols(&self) -> &'static phf::Map<&'static str, TokenType> {
+        match self {
+            Dialect::Vanilla | Dialect::CLike => &SYMBOLS,
+            Dialect::Legacy => &LEGACY_SYMBOLS,
+        }
+    }
+}
+
+impl fmt::Display for Dialect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Dialect {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Dialect::ALL
+            .into_iter()
+            .find(|dialect| dialect.name() == s)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Dialect::ALL.iter().map(|d| d.name()).collect();
+                format!(
+                    "Dialecto '{}' desconocido, usa uno de: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
@@ -0,0 +1,104 @@
+use crate::{
+    codes,
+    data::{Cursor, Error, Severity},
+    dialect::Dialect,
+    options::LexerOptions,
+};
+
+/// Prefijo de la directiva de cabecera: `// vanilla: dialect=legacy, nested-comments`
+pub const HEADER_PREFIX: &str = "vanilla:";
+
+/// Opciones con las que se tokeniza `contents`: las recibidas más las de la directiva de la primera
+/// línea, si la hay. Las claves desconocidas o con valores inválidos se ignoran.
+pub fn effective_options(contents: &str, options: &LexerOptions) -> LexerOptions {
+    header_options(contents, options).0
+}
+
+/// Igual que `effective_options`, pero también regresa una advertencia por cada clave de la
+/// directiva que no se pudo aplicar
+pub fn header_options(contents: &str, options: &LexerOptions) -> (LexerOptions, Vec<Error>) {
+    let mut effective = options.clone();
+    let mut diagnostics = Vec::new();
+    // la primera línea termina en `\n`, `\r\n` o `\r`, igual que para el scanner
+    let line = contents.split(['\r', '\n']).next().unwrap_or("");
+    let Some(directives) = header_directives(line) else {
+        return (effective, diagnostics);
+    };
+    let mut offset = line.len() - directives.len();
+    for item in directives.split(',') {
+        let directive = item.trim();
+        let start = offset + (item.len() - item.trim_start().len());
+        offset += item.len() + 1;
+        if directive.is_empty() {
+            continue;
+        }
+        if let Err(message) = apply_directive(&mut effective, directive) {
+            let col = line[..start].chars().count() as i32 + 1;
+            diagnostics.push(Error {
+                start: Cursor { col, lin: 1 },
+                end: Cursor {
+                    col: col + directive.chars().count() as i32 - 1,
+                    lin: 1,
+                },
+                message,
+                lexemme: directive.to_string(),
+                suggestion: None,
+                severity: Severity::Warning,
+                code: codes::UNKNOWN_DIRECTIVE.to_string(),
+            });
+        }
+    }
+    (effective, diagnostics)
+}
+
+/// Lista de directivas después de `// vanilla:`
+fn header_directives(line: &str) -> Option<&str> {
+    line.strip_prefix("//")?
+        .trim_start()
+        .strip_prefix(HEADER_PREFIX)
+}
+
+/// Aplica una directiva `clave` o `clave=valor`
+fn apply_directive(options: &mut LexerOptions, directive: &str) -> Result<(), String> {
+    let (key, value) = match directive.split_once('=') {
+        Some((key, value)) => (key.trim(), Some(value.trim())),
+        None => (directive, None),
+    };
+    let flag = || match value {
+        None | Some("true") => Ok(true),
+        Some("false") => Ok(false),
+        Some(value) => Err(format!(
+            "Valor '{}' inválido para '{}', usa true o false",
+            value, key
+        )),
+    };
+    let required = || {
+        value
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| format!("La directiva '{}' necesita un valor", key))
+    };
+    match key {
+        "dialect" => {
+            let dialect: Dialect = required()?.parse()?;
+            // las tablas del dialecto se aplican sobre los cambios que ya traen las opciones
+            options.config.switch_dialect(dialect);
+            options.c_operators |= dialect == Dialect::CLike;
+        }
+        "signed-numbers" => options.signed_numbers = flag()?,
+        "nested-comments" => options.nested_comments = flag()?,
+        "none-tokens" => options.none_tokens = flag()?,
+        "c-operators" => options.c_operators = flag()?,
+        "case-insensitive-keywords" => options.case_insensitive_keywords = flag()?,
+        "unicode-identifiers" => options.unicode_identifiers = flag()?,
+        "warn" => options.lints.enable(required()?)?,
+        "max-identifier-length" => {
+            let value = required()?;
+            let length = value
+                .parse()
+                .map_err(|_| format!("Longitud '{}' inválida", value))?;
+            options.lints.max_identifier_length = Some(length);
+        }
+        _ => return Err(format!("Directiva '{}' desconocida", key)),
+    }
+    Ok(())
+}
@@ -0,0 +1,199 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    codes,
+    data::{Cursor, Error, Severity},
+    line_endings::{lines_with_endings, LineEnding},
+    utils::unknown_symbols_message,
+};
+
+/// Codificación con la que se leen los archivos
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SourceEncoding {
+    /// UTF-8; cada secuencia inválida se reemplaza por U+FFFD y se reporta como error
+    #[default]
+    Utf8,
+    /// ISO-8859-1: cada byte es el carácter con el mismo código
+    Latin1,
+    /// Latin-1 con los caracteres de Windows en 0x80-0x9F (comillas tipográficas, `€`, ...)
+    Windows1252,
+}
+
+impl SourceEncoding {
+    pub const ALL: [SourceEncoding; 3] = [
+        SourceEncoding::Utf8,
+        SourceEncoding::Latin1,
+        SourceEncoding::Windows1252,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SourceEncoding::Utf8 => "utf-8",
+            SourceEncoding::Latin1 => "latin1",
+            SourceEncoding::Windows1252 => "windows-1252",
+        }
+    }
+}
+
+impl Display for SourceEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for SourceEncoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "utf-8" | "utf8" => Ok(SourceEncoding::Utf8),
+            "latin1" | "latin-1" | "iso-8859-1" => Ok(SourceEncoding::Latin1),
+            "windows-1252" | "cp1252" => Ok(SourceEncoding::Windows1252),
+            _ => Err(format!(
+                "Codificación '{}' desconocida, usa una de: {}",
+                s,
+                SourceEncoding::ALL
+                    .map(|encoding| encoding.name())
+                    .join(", ")
+            )),
+        }
+    }
+}
+
+/// Caracteres de windows-1252 en 0x80-0x9F; los códigos sin asignar se quedan como controles C1
+const WINDOWS_1252: [char; 32] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8D}', 'Ž', '\u{8F}',
+    '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9D}', 'ž', 'Ÿ',
+];
+
+/// Secuencia de bytes que no es UTF-8 válido, reemplazada por un U+FFFD en el texto
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidSequence {
+    /// Posición en bytes del U+FFFD dentro del texto decodificado
+    pub offset: usize,
+    pub bytes: Vec<u8>,
+}
+
+/// Texto decodificado y las secuencias que no se pudieron decodificar
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Decoded {
+    pub text: String,
+    pub invalid: Vec<InvalidSequence>,
+}
+
+/// Decodifica `bytes` sin fallar nunca
+pub fn decode(bytes: &[u8], encoding: SourceEncoding) -> Decoded {
+    let mut decoded = Decoded::default();
+    match encoding {
+        SourceEncoding::Utf8 => {
+            for chunk in bytes.utf8_chunks() {
+                decoded.text.push_str(chunk.valid());
+                if !chunk.invalid().is_empty() {
+                    decoded.invalid.push(InvalidSequence {
+                        offset: decoded.text.len(),
+                        bytes: chunk.invalid().to_vec(),
+                    });
+                    decoded.text.push(char::REPLACEMENT_CHARACTER);
+                }
+            }
+        }
+        SourceEncoding::Latin1 => decoded.text = bytes.iter().map(|b| *b as char).collect(),
+        SourceEncoding::Windows1252 => {
+            decoded.text = bytes
+                .iter()
+                .map(|b| match b {
+                    0x80..=0x9F => WINDOWS_1252[(b - 0x80) as usize],
+                    _ => *b as char,
+                })
+                .collect()
+        }
+    }
+    decoded
+}
+
+/// Un error por cada secuencia inválida. `skipped` son los bytes que se quitaron al inicio del
+/// texto (el BOM) antes de escanearlo.
+pub fn invalid_sequence_errors(
+    text: &str,
+    invalid: &[InvalidSequence],
+    skipped: usize,
+) -> Vec<Error> {
+    let lines = lines_with_endings(text);
+    invalid
+        .iter()
+        .filter_map(|sequence| {
+            let start = cursor_at(text, &lines, sequence.offset.checked_sub(skipped)?);
+            let bytes: Vec<String> = sequence
+                .bytes
+                .iter()
+                .map(|b| format!("0x{:02X}", b))
+                .collect();
+            Some(Error {
+                end: Cursor {
+                    col: start.col + 1,
+                    lin: start.lin,
+                },
+                start,
+                message: format!("Secuencia UTF-8 inválida: {}", bytes.join(" ")),
+                lexemme: char::REPLACEMENT_CHARACTER.to_string(),
+                suggestion: None,
+                severity: Severity::Error,
+                code: codes::INVALID_UTF8.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Quita de un error de símbolos desconocidos los U+FFFD que ya se reportan como secuencia inválida
+/// (`replaced` dice si hay una en esa posición). Regresa `None` si no queda ningún símbolo.
+pub fn without_replaced_symbols(
+    mut error: Error,
+    replaced: &impl Fn(&Cursor) -> bool,
+) -> Option<Error> {
+    if error.code != codes::UNKNOWN_SYMBOL {
+        return Some(error);
+    }
+    // los símbolos desconocidos nunca cruzan de línea
+    let symbols: String = error
+        .lexemme
+        .chars()
+        .enumerate()
+        .filter(|(i, c)| {
+            let cursor = Cursor {
+                col: error.start.col + *i as i32,
+                lin: error.start.lin,
+            };
+            *c != char::REPLACEMENT_CHARACTER || !replaced(&cursor)
+        })
+        .map(|(_, c)| c)
+        .collect();
+    if symbols.is_empty() {
+        return None;
+    }
+    if symbols.len() != error.lexemme.len() {
+        error.message = unknown_symbols_message(&symbols);
+    }
+    Some(error)
+}
+
+/// Cursor (en caracteres) del byte `offset` de `text`
+fn cursor_at(text: &str, lines: &[(&str, Option<LineEnding>)], offset: usize) -> Cursor {
+    let mut line_start = 0;
+    for (index, (line, ending)) in lines.iter().enumerate() {
+        let line_end = line_start + line.len();
+        if offset <= line_end {
+            return Cursor {
+                col: text[line_start..offset].chars().count() as i32 + 1,
+                lin: index as i32 + 1,
+            };
+        }
+        line_start = line_end + ending.map_or(0, |ending| ending.as_str().len());
+    }
+    Cursor {
+        col: 1,
+        lin: lines.len() as i32,
+    }
+}
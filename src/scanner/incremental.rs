@@ -0,0 +1,349 @@
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    data::{Cursor, Error, Token, TokenType},
+    directives::effective_options,
+    get_token_ref,
+    line_endings::BOM,
+    options::LexerOptions,
+    positions::PositionEncoding,
+    pragmas::{parse_pragma, Suppressions},
+    tokenize_with,
+    utils::{advance_line_break, can_end_expression, init_cursor, unknown_symbols_error},
+    with_suggestion,
+};
+
+/// Cambio en el texto: se reemplaza lo que hay de `start` a `end` (sin incluir) por `text`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Edit {
+    pub start: Cursor,
+    pub end: Cursor,
+    pub text: String,
+}
+
+/// Texto ya tokenizado que se puede editar volviendo a escanear solo la región afectada. Después
+/// de cada edición `tokens` y `errors` son iguales a los de `tokenize_with` sobre el texto nuevo.
+#[derive(Debug, Clone)]
+pub struct Document {
+    text: String,
+    options: LexerOptions,
+    tokens: Vec<Token>,
+    errors: Vec<Error>,
+    /// Errores antes de aplicar los pragmas; se vuelven a filtrar después de cada edición
+    unfiltered: Vec<Error>,
+    /// Comentarios con pragmas, en orden
+    pragmas: Vec<Token>,
+}
+
+impl Document {
+    pub fn new(text: String, options: LexerOptions) -> Self {
+        let mut document = Document {
+            text,
+            options,
+            tokens: Vec::new(),
+            errors: Vec::new(),
+            unfiltered: Vec::new(),
+            pragmas: Vec::new(),
+        };
+        document.rescan();
+        document
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+
+    /// Aplica `edit` y regresa los índices de los tokens que se volvieron a escanear; el resto se
+    /// conservan, recorridos a su nueva posición. Falla si el rango no está dentro del texto.
+    pub fn apply(&mut self, edit: &Edit) -> Result<Range<usize>, String> {
+        let start = cursor_offset(&self.text, &edit.start);
+        let end = cursor_offset(&self.text, &edit.end);
+        let (start, end) = match (start, end) {
+            (Some(start), Some(end)) if start <= end => (start, end),
+            _ => {
+                return Err(format!(
+                    "El rango {}:{} a {}:{} no está dentro del texto",
+                    edit.start.lin, edit.start.col, edit.end.lin, edit.end.col
+                ))
+            }
+        };
+        let old_options = self.header_options();
+        let old_text = std::mem::take(&mut self.text);
+        self.text = format!("{}{}{}", &old_text[..start], edit.text, &old_text[end..]);
+        let options = self.header_options();
+        if self.needs_full_scan(&old_text, start, end, edit) || options != old_options {
+            self.rescan();
+            return Ok(0..self.tokens.len());
+        }
+
+        // se conservan los tokens que terminan antes de la edición: el scanner solo ve un
+        // caracter después del token para decidir dónde termina
+        let kept = self
+            .tokens
+            .iter()
+            .take_while(|token| before(&token.end, &edit.start))
+            .count();
+        let Some(restart) = kept
+            .checked_sub(1)
+            .map(|last| self.tokens[last].end.clone())
+        else {
+            self.rescan();
+            return Ok(0..self.tokens.len());
+        };
+        let old_tokens = self.tokens.split_off(kept);
+        let kept_errors = self
+            .unfiltered
+            .iter()
+            .take_while(|error| before(&error.start, &restart))
+            .count();
+        let old_errors = self.unfiltered.split_off(kept_errors);
+        let kept_pragmas = self
+            .pragmas
+            .iter()
+            .take_while(|pragma| before(&pragma.start, &restart))
+            .count();
+        let old_pragmas = self.pragmas.split_off(kept_pragmas);
+
+        let mut new_end = edit.start.clone();
+        advance(&mut new_end, &edit.text);
+        let shift = |cursor: &Cursor| shift_cursor(cursor, &edit.end, &new_end);
+        // primer token viejo que empieza después de la edición
+        let mut old = old_tokens
+            .iter()
+            .position(|token| !before(&token.start, &edit.end))
+            .unwrap_or(old_tokens.len());
+        let last_kept = self.tokens.last().cloned();
+
+        let text = std::mem::take(&mut self.text);
+        let rem_text = &text[cursor_offset(&old_text, &restart).unwrap_or(start)..];
+        let synced = self.lex(rem_text, restart, &options, |token, last| {
+            // desde un token que empieza en la misma posición del texto sin cambios y con un
+            // token anterior equivalente, el resto del escaneo es igual al anterior
+            if before(&token.start, &new_end) {
+                return None;
+            }
+            while old < old_tokens.len() && before(&shift(&old_tokens[old].start), &token.start) {
+                old += 1;
+            }
+            (old < old_tokens.len()
+                && shift(&old_tokens[old].start) == token.start
+                && old_tokens[old].token_type == token.token_type
+                && old_tokens[old].lexemme == token.lexemme
+                && same_sign_context(
+                    last,
+                    old.checked_sub(1)
+                        .map_or(last_kept.as_ref(), |i| old_tokens.get(i)),
+                ))
+            .then_some(old)
+        });
+        self.text = text;
+        let relexed = kept..self.tokens.len();
+        if let Some(synced) = synced {
+            let sync_start = old_tokens[synced].start.clone();
+            self.tokens
+                .extend(old_tokens.into_iter().skip(synced).map(|mut token| {
+                    token.start = shift(&token.start);
+                    token.end = shift(&token.end);
+                    token
+                }));
+            self.pragmas.extend(
+                old_pragmas
+                    .into_iter()
+                    .skip_while(|pragma| before(&pragma.start, &sync_start))
+                    .map(|mut pragma| {
+                        pragma.start = shift(&pragma.start);
+                        pragma.end = shift(&pragma.end);
+                        pragma
+                    }),
+            );
+            // las sugerencias solo cubren hasta un caracter después de su error, así que ninguna
+            // de antes del token de sincronización cambia las de después
+            self.unfiltered.extend(
+                old_errors
+                    .into_iter()
+                    .skip_while(|error| before(&error.start, &sync_start))
+                    .map(|mut error| {
+                        error.start = shift(&error.start);
+                        error.end = shift(&error.end);
+                        if let Some(suggestion) = error.suggestion.as_mut() {
+                            suggestion.start = shift(&suggestion.start);
+                            suggestion.end = shift(&suggestion.end);
+                        }
+                        error
+                    }),
+            );
+        }
+        // un pragma que cambia o que queda en otra línea afecta errores fuera de la edición
+        self.filter_errors();
+        Ok(relexed)
+    }
+
+    /// Opciones con la directiva de la primera línea aplicada
+    fn header_options(&self) -> LexerOptions {
+        effective_options(
+            self.text.strip_prefix(BOM).unwrap_or(&self.text),
+            &self.options,
+        )
+    }
+
+    /// Vuelve a escanear todo el texto
+    fn rescan(&mut self) {
+        if self.options.position_encoding != PositionEncoding::Chars {
+            (self.tokens, self.errors) = tokenize_with(&self.text, &self.options);
+            return;
+        }
+        let options = self.header_options();
+        self.tokens.clear();
+        self.unfiltered.clear();
+        self.pragmas.clear();
+        let text = std::mem::take(&mut self.text);
+        self.lex(
+            text.strip_prefix(BOM).unwrap_or(&text),
+            init_cursor(),
+            &options,
+            |_, _| None,
+        );
+        self.text = text;
+        self.filter_errors();
+    }
+
+    /// Escanea `text` desde `cursor` agregando tokens, errores y pragmas, como `tokenize_with`,
+    /// hasta el final o hasta que `synced` regrese el índice del token viejo con el que coincide
+    /// un token nuevo
+    fn lex(
+        &mut self,
+        text: &str,
+        mut cursor: Cursor,
+        options: &LexerOptions,
+        mut synced: impl FnMut(&Token, Option<&Token>) -> Option<usize>,
+    ) -> Option<usize> {
+        let mut rem_text = text;
+        loop {
+            let prev = self.tokens.last().map(|t| &t.token_type);
+            let (result, string) = get_token_ref(rem_text, &mut cursor, options, prev);
+            rem_text = string;
+            let token = match result {
+                Ok(token) if token.token_type == TokenType::EOF => return None,
+                Ok(token) => token.into_owned(),
+                Err(error) => {
+                    let error = with_suggestion(self.unfiltered.last(), error, rem_text);
+                    self.unfiltered.push(error);
+                    continue;
+                }
+            };
+            if matches!(
+                token.token_type,
+                TokenType::BLOCK_COMMENT | TokenType::INLINE_COMMENT
+            ) && parse_pragma(&token.lexemme).is_some()
+            {
+                self.pragmas.push(token);
+                continue;
+            }
+            if matches!(
+                token.token_type,
+                TokenType::BLOCK_COMMENT | TokenType::INLINE_COMMENT | TokenType::DOC_COMMENT
+            ) {
+                continue;
+            }
+            if let Some(old) = synced(&token, self.tokens.last()) {
+                return Some(old);
+            }
+            if matches!(token.token_type, TokenType::NONE) {
+                let error = with_suggestion(
+                    self.unfiltered.last(),
+                    unknown_symbols_error(&token),
+                    rem_text,
+                );
+                self.unfiltered.push(error);
+            }
+            self.tokens.push(token);
+        }
+    }
+
+    /// Quita de `errors` los que suprimen los pragmas
+    fn filter_errors(&mut self) {
+        let mut suppressions = Suppressions::default();
+        for pragma in self.pragmas.iter() {
+            // los pragmas inválidos solo generan advertencias, que `errors` no incluye
+            let _ = suppressions.add_comment(&pragma.as_token_ref());
+        }
+        self.errors = self
+            .unfiltered
+            .iter()
+            .filter(|error| !suppressions.is_suppressed(error))
+            .cloned()
+            .collect();
+    }
+
+    /// Casos en los que una edición puede cambiar tokens lejos de ella: las columnas que no son
+    /// caracteres dependen del texto anterior y un `\r\n` puede unirse o separarse. Los cambios
+    /// en la directiva de la primera línea se detectan comparando las opciones.
+    fn needs_full_scan(&self, old_text: &str, start: usize, end: usize, edit: &Edit) -> bool {
+        self.options.position_encoding != PositionEncoding::Chars
+            || old_text[..start].ends_with('\r')
+            || old_text[end..].starts_with('\n')
+            || edit.text.ends_with('\r')
+    }
+}
+
+/// El signo de un número solo depende de si el token anterior puede terminar una expresión
+fn same_sign_context(a: Option<&Token>, b: Option<&Token>) -> bool {
+    a.is_some_and(|a| can_end_expression(&a.token_type))
+        == b.is_some_and(|b| can_end_expression(&b.token_type))
+}
+
+fn before(a: &Cursor, b: &Cursor) -> bool {
+    (a.lin, a.col) < (b.lin, b.col)
+}
+
+/// Posición de `cursor` después de la edición, para posiciones que estaban después de `old_end`
+fn shift_cursor(cursor: &Cursor, old_end: &Cursor, new_end: &Cursor) -> Cursor {
+    if cursor.lin == old_end.lin {
+        Cursor {
+            col: cursor.col - old_end.col + new_end.col,
+            lin: new_end.lin,
+        }
+    } else {
+        Cursor {
+            col: cursor.col,
+            lin: cursor.lin - old_end.lin + new_end.lin,
+        }
+    }
+}
+
+/// Mueve el cursor al final de `text`
+fn advance(cursor: &mut Cursor, text: &str) {
+    for (i, c) in text.char_indices() {
+        cursor.col += 1;
+        advance_line_break(c, &text[i..], cursor);
+    }
+}
+
+/// Byte de `text` en el que está `cursor`, sin contar el BOM
+fn cursor_offset(text: &str, target: &Cursor) -> Option<usize> {
+    let skipped = if text.starts_with(BOM) {
+        BOM.len_utf8()
+    } else {
+        0
+    };
+    let body = &text[skipped..];
+    let mut cursor = Cursor { col: 1, lin: 1 };
+    for (i, c) in body.char_indices() {
+        if cursor == *target {
+            return Some(skipped + i);
+        }
+        cursor.col += 1;
+        advance_line_break(c, &body[i..], &mut cursor);
+    }
+    (cursor == *target).then_some(text.len())
+}
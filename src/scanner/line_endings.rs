@@ -0,0 +1,181 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    codes,
+    data::{Cursor, Error, Severity, Suggestion},
+};
+
+/// Marca de orden de bytes (BOM) de UTF-8; si está al inicio del archivo se ignora
+pub const BOM: char = '\u{FEFF}';
+
+/// Estilo de salto de línea
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    Lf,
+    CrLf,
+    Cr,
+}
+
+impl LineEnding {
+    pub const ALL: [LineEnding; 3] = [LineEnding::Lf, LineEnding::CrLf, LineEnding::Cr];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+            LineEnding::Cr => "\r",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "LF",
+            LineEnding::CrLf => "CRLF",
+            LineEnding::Cr => "CR",
+        }
+    }
+}
+
+impl Display for LineEnding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl std::str::FromStr for LineEnding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        LineEnding::ALL
+            .into_iter()
+            .find(|ending| ending.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Fin de línea '{}' desconocido, usa lf, crlf o cr", s))
+    }
+}
+
+/// BOM y saltos de línea encontrados en un archivo
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceFormat {
+    pub bom: bool,
+    pub lf: usize,
+    pub crlf: usize,
+    pub cr: usize,
+}
+
+impl SourceFormat {
+    pub fn detect(contents: &str) -> Self {
+        let mut format = SourceFormat {
+            bom: contents.starts_with(BOM),
+            ..Default::default()
+        };
+        let bytes = contents.as_bytes();
+        for (i, b) in bytes.iter().enumerate() {
+            match b {
+                b'\n' if i > 0 && bytes[i - 1] == b'\r' => format.crlf += 1,
+                b'\n' => format.lf += 1,
+                b'\r' if bytes.get(i + 1) != Some(&b'\n') => format.cr += 1,
+                _ => {}
+            }
+        }
+        format
+    }
+
+    /// Estilo más usado; en empate gana LF, luego CRLF. `None` si no hay saltos de línea.
+    pub fn line_ending(&self) -> Option<LineEnding> {
+        let counts = [
+            (self.lf, LineEnding::Lf),
+            (self.crlf, LineEnding::CrLf),
+            (self.cr, LineEnding::Cr),
+        ];
+        counts
+            .into_iter()
+            .filter(|(count, _)| *count > 0)
+            .rev()
+            .max_by_key(|(count, _)| *count)
+            .map(|(_, ending)| ending)
+    }
+
+    /// Indica si el archivo usa más de un estilo de salto de línea
+    pub fn is_mixed(&self) -> bool {
+        [self.lf, self.crlf, self.cr]
+            .iter()
+            .filter(|count| **count > 0)
+            .count()
+            > 1
+    }
+}
+
+/// Líneas del texto junto con el salto que las termina (`None` en la última si no tiene).
+/// A diferencia de `str::lines`, un `\r` solo también termina la línea.
+pub fn lines_with_endings(contents: &str) -> Vec<(&str, Option<LineEnding>)> {
+    let mut lines = Vec::new();
+    let mut rest = contents;
+    while let Some(index) = rest.find(['\r', '\n']) {
+        let (line, tail) = rest.split_at(index);
+        let ending = if tail.starts_with("\r\n") {
+            LineEnding::CrLf
+        } else if tail.starts_with('\r') {
+            LineEnding::Cr
+        } else {
+            LineEnding::Lf
+        };
+        lines.push((line, Some(ending)));
+        rest = &tail[ending.as_str().len()..];
+    }
+    lines.push((rest, None));
+    lines
+}
+
+/// Reemplaza todos los saltos de línea por `ending`
+pub fn normalize_line_endings(contents: &str, ending: LineEnding) -> String {
+    let mut normalized = String::with_capacity(contents.len());
+    for (line, line_ending) in lines_with_endings(contents) {
+        normalized.push_str(line);
+        if line_ending.is_some() {
+            normalized.push_str(ending.as_str());
+        }
+    }
+    normalized
+}
+
+/// Una advertencia por cada salto de línea distinto al más usado en el archivo
+pub fn mixed_line_ending_warnings(contents: &str) -> Vec<Error> {
+    let format = SourceFormat::detect(contents);
+    let Some(expected) = format.line_ending().filter(|_| format.is_mixed()) else {
+        return Vec::new();
+    };
+    let mut warnings = Vec::new();
+    for (index, (line, ending)) in lines_with_endings(contents).into_iter().enumerate() {
+        let Some(ending) = ending.filter(|ending| *ending != expected) else {
+            continue;
+        };
+        let lin = index as i32 + 1;
+        let col = line.chars().count() as i32 + 1;
+        let start = Cursor { col, lin };
+        let end = Cursor {
+            col: col + ending.as_str().len() as i32 - 1,
+            lin,
+        };
+        warnings.push(Error {
+            start: start.clone(),
+            end: end.clone(),
+            message: format!(
+                "Salto de línea {} en un archivo que usa {} ({} LF, {} CRLF, {} CR)",
+                ending, expected, format.lf, format.crlf, format.cr
+            ),
+            lexemme: ending.as_str().to_string(),
+            suggestion: Some(Suggestion {
+                message: format!("Usa {}", expected),
+                replacement: expected.as_str().to_string(),
+                start,
+                end,
+            }),
+            severity: Severity::Warning,
+            code: codes::MIXED_LINE_ENDINGS.to_string(),
+        });
+    }
+    warnings
+}
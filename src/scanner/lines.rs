@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    codes,
+    data::{Token, TokenType},
+    get_token_ref,
+    options::LexerOptions,
+    utils::{can_end_expression, init_cursor},
+};
+
+/// Estado del scanner al inicio o al final de una línea. Entre líneas la máquina de estados solo
+/// puede estar en `START` o dentro de un comentario de bloque, y el signo de un número depende del
+/// último token significativo.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct LineState {
+    /// Comentarios de bloque abiertos; más de uno solo con `nested_comments`
+    pub comment_depth: usize,
+    /// El último token significativo puede terminar una expresión, así que un `+`/`-` al inicio de
+    /// la siguiente línea es un operador aunque esté activo `signed_numbers`
+    pub after_operand: bool,
+}
+
+impl LineState {
+    pub fn in_comment(&self) -> bool {
+        self.comment_depth > 0
+    }
+}
+
+/// Tokeniza una sola línea empezando en `entering`, para resaltar sintaxis línea por línea. A
+/// diferencia de `tokenize` se regresan también los comentarios (el pedazo de un comentario de
+/// bloque que cae en la línea es un `BLOCK_COMMENT`) y los errores se regresan como tokens `NONE`.
+/// Las columnas son relativas a la línea, que siempre es la línea 1; el salto de línea final de
+/// `text`, si lo tiene, se ignora.
+pub fn lex_line(
+    text: &str,
+    entering: LineState,
+    options: &LexerOptions,
+) -> (Vec<Token>, LineState) {
+    let text = text.trim_end_matches(['\r', '\n']);
+    let mut tokens = Vec::new();
+    let mut state = entering;
+    let mut cursor = init_cursor();
+    let mut rest = text;
+    if state.in_comment() {
+        let (end, depth) = comment_end(rest, state.comment_depth, options.nested_comments);
+        let lexemme = &rest[..end];
+        let start = cursor.clone();
+        cursor.col += lexemme.chars().count() as i32;
+        tokens.push(Token {
+            token_type: TokenType::BLOCK_COMMENT,
+            lexemme: lexemme.to_string(),
+            start,
+            end: cursor.clone(),
+        });
+        state.comment_depth = depth;
+        rest = &rest[end..];
+    }
+    while !state.in_comment() {
+        let prev = state.after_operand.then_some(&TokenType::ID);
+        let (result, remaining) = get_token_ref(rest, &mut cursor, options, prev);
+        rest = remaining;
+        let token = match result {
+            Ok(token) if token.token_type == TokenType::EOF => break,
+            Ok(token) => {
+                // igual que en `tokenize`, los comentarios y errores no cambian el token anterior
+                if !matches!(
+                    token.token_type,
+                    TokenType::BLOCK_COMMENT | TokenType::INLINE_COMMENT | TokenType::DOC_COMMENT
+                ) {
+                    state.after_operand = can_end_expression(&token.token_type);
+                }
+                token.into_owned()
+            }
+            Err(error) if error.code == codes::UNTERMINATED_COMMENT => {
+                // el comentario sigue en la siguiente línea
+                let body = error.lexemme.strip_prefix("/*").unwrap_or(&error.lexemme);
+                state.comment_depth = comment_end(body, 1, options.nested_comments).1;
+                Token {
+                    token_type: TokenType::BLOCK_COMMENT,
+                    lexemme: error.lexemme,
+                    start: error.start,
+                    end: error.end,
+                }
+            }
+            Err(error) => Token {
+                token_type: TokenType::NONE,
+                lexemme: error.lexemme,
+                start: error.start,
+                end: error.end,
+            },
+        };
+        tokens.push(token);
+    }
+    (tokens, state)
+}
+
+/// Estados de salida de cada línea de `contents`: el de la línea `i` es el de entrada de la `i + 1`
+pub fn line_states(contents: &str, options: &LexerOptions) -> Vec<LineState> {
+    let mut state = LineState::default();
+    crate::line_endings::lines_with_endings(contents)
+        .into_iter()
+        .map(|(line, _)| {
+            state = lex_line(line, state, options).1;
+            state
+        })
+        .collect()
+}
+
+/// Dónde termina un comentario de bloque que ya tiene `depth` aperturas: regresa el byte después
+/// del `*/` que lo cierra (o el final del texto) y las aperturas que quedan
+fn comment_end(text: &str, mut depth: usize, nested: bool) -> (usize, usize) {
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let next = chars.peek().map(|(_, c)| *c);
+        if c == '*' && next == Some('/') {
+            chars.next();
+            depth -= 1;
+            if depth == 0 {
+                return (i + 2, 0);
+            }
+        } else if nested && c == '/' && next == Some('*') {
+            chars.next();
+            depth += 1;
+        }
+    }
+    (text.len(), depth)
+}
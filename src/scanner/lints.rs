@@ -0,0 +1,172 @@
+use crate::{
+    codes,
+    data::{Cursor, Error, Severity, Suggestion, Token, TokenType},
+    line_endings::lines_with_endings,
+    options::{LexerOptions, LintOptions},
+    tokenize_with,
+};
+
+/// Advertencias que dependen de un solo token: identificadores largos, ceros a la izquierda y
+/// código comentado
+pub fn token_warnings(token: &Token, options: &LexerOptions) -> Vec<Error> {
+    let lints = &options.lints;
+    let mut warnings = Vec::new();
+    match token.token_type {
+        TokenType::ID => {
+            let length = token.lexemme.chars().count();
+            if let Some(max) = lints.max_identifier_length.filter(|max| length > *max) {
+                warnings.push(warning(
+                    token,
+                    codes::LONG_IDENTIFIER,
+                    format!(
+                        "El identificador '{}' tiene {} caracteres, el máximo es {}",
+                        token.lexemme, length, max
+                    ),
+                    None,
+                ));
+            }
+        }
+        TokenType::INT | TokenType::FLOAT if lints.leading_zeros => {
+            let sign_len = token.lexemme.len() - token.lexemme.trim_start_matches(['+', '-']).len();
+            let (sign, number) = token.lexemme.split_at(sign_len);
+            let int_part = number.split('.').next().unwrap_or(number);
+            if int_part.len() > 1 && int_part.starts_with('0') {
+                let mut fixed = number.trim_start_matches('0');
+                if fixed.is_empty() || fixed.starts_with('.') {
+                    fixed = &number[int_part.len() - 1..];
+                }
+                let replacement = format!("{}{}", sign, fixed);
+                warnings.push(warning(
+                    token,
+                    codes::LEADING_ZEROS,
+                    format!("El número '{}' tiene ceros a la izquierda", token.lexemme),
+                    Some(replacement),
+                ));
+            }
+        }
+        TokenType::INLINE_COMMENT | TokenType::BLOCK_COMMENT
+            if lints.commented_out_code
+                && looks_like_code(comment_body(&token.lexemme), options) =>
+        {
+            warnings.push(warning(
+                token,
+                codes::COMMENTED_OUT_CODE,
+                "El comentario parece código comentado".to_string(),
+                None,
+            ));
+        }
+        _ => {}
+    }
+    warnings
+}
+
+/// Advertencias sobre los espacios de cada línea: indentación mixta y espacios al final
+pub fn line_warnings(contents: &str, lints: &LintOptions) -> Vec<Error> {
+    let mut warnings = Vec::new();
+    if !lints.mixed_indentation && !lints.trailing_whitespace {
+        return warnings;
+    }
+    for (index, (line, _)) in lines_with_endings(contents).into_iter().enumerate() {
+        let lin = index as i32 + 1;
+        let content = line.trim_start_matches([' ', '\t']);
+        let indent = &line[..line.len() - content.len()];
+        if lints.mixed_indentation && indent.contains(' ') && indent.contains('\t') {
+            warnings.push(line_warning(
+                lin,
+                (1, indent.chars().count()),
+                indent,
+                codes::MIXED_INDENTATION,
+                "La indentación mezcla tabuladores y espacios".to_string(),
+                None,
+            ));
+        }
+        let trimmed = line.trim_end_matches([' ', '\t']);
+        if lints.trailing_whitespace && trimmed.len() < line.len() {
+            let start = trimmed.chars().count() + 1;
+            let trailing = &line[trimmed.len()..];
+            warnings.push(line_warning(
+                lin,
+                (start, start + trailing.chars().count() - 1),
+                trailing,
+                codes::TRAILING_WHITESPACE,
+                "Espacios al final de la línea".to_string(),
+                Some(String::new()),
+            ));
+        }
+    }
+    warnings
+}
+
+/// Texto de un comentario sin sus delimitadores
+fn comment_body(lexemme: &str) -> &str {
+    if let Some(body) = lexemme.strip_prefix("//") {
+        return body;
+    }
+    let body = lexemme.strip_prefix("/*").unwrap_or(lexemme);
+    body.strip_suffix("*/").unwrap_or(body)
+}
+
+/// Un comentario parece código si termina como una instrucción o bloque y se puede escanear
+/// sin errores en al menos dos tokens
+fn looks_like_code(body: &str, options: &LexerOptions) -> bool {
+    let body = body.trim();
+    if !(body.ends_with(';') || body.ends_with('{') || body.ends_with('}')) {
+        return false;
+    }
+    let options = LexerOptions {
+        lints: LintOptions::default(),
+        ..options.clone()
+    };
+    let (tokens, errors) = tokenize_with(body, &options);
+    errors.is_empty() && tokens.len() >= 2
+}
+
+fn warning(token: &Token, code: &str, message: String, replacement: Option<String>) -> Error {
+    Error {
+        start: token.start.clone(),
+        end: token.end.clone(),
+        message,
+        lexemme: token.lexemme.clone(),
+        suggestion: replacement.map(|replacement| Suggestion {
+            message: format!("Escribe '{}'", replacement),
+            replacement,
+            start: token.start.clone(),
+            end: token.end.clone(),
+        }),
+        severity: Severity::Warning,
+        code: code.to_string(),
+    }
+}
+
+/// Advertencia sobre las columnas `cols` (inclusivas) de la línea `lin`
+fn line_warning(
+    lin: i32,
+    cols: (usize, usize),
+    lexemme: &str,
+    code: &str,
+    message: String,
+    replacement: Option<String>,
+) -> Error {
+    let start = Cursor {
+        col: cols.0 as i32,
+        lin,
+    };
+    let end = Cursor {
+        col: cols.1 as i32 + 1,
+        lin,
+    };
+    Error {
+        suggestion: replacement.map(|replacement| Suggestion {
+            message: "Quita los espacios".to_string(),
+            replacement,
+            start: start.clone(),
+            end: end.clone(),
+        }),
+        start,
+        end,
+        message,
+        lexemme: lexemme.to_string(),
+        severity: Severity::Warning,
+        code: code.to_string(),
+    }
+}
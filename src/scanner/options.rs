@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
+use unicode_xid::UnicodeXID;
+
+use crate::{
+    data::TokenType, dialect::Dialect, encoding::SourceEncoding, positions::PositionEncoding,
+};
+
+/// Opciones que modifican el comportamiento del scanner. El valor por defecto
+/// reproduce el comportamiento original de `get_token`/`tokenize`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct LexerOptions {
+    /// Une un `+`/`-` con el número que le sigue (`x = -3` produce `INT("-3")`)
+    /// cuando el token significativo anterior no puede terminar una expresión.
+    pub signed_numbers: bool,
+    /// Permite anidar comentarios de bloque: `/* a /* b */ c */` es un solo `BLOCK_COMMENT`.
+    pub nested_comments: bool,
+    /// Agrupa los caracteres no permitidos consecutivos en un solo token `NONE` en lugar de
+    /// solo reportar un error; `tokenize` agrega un único error por cada token `NONE`.
+    pub none_tokens: bool,
+    /// Reconoce `&&` y `||` como `AND` y `OR`, conservando el lexema original.
+    pub c_operators: bool,
+    /// Reconoce palabras reservadas sin importar mayúsculas (`WHILE`, `Main`), con una advertencia
+    /// cuando no se escriben como en la tabla.
+    pub case_insensitive_keywords: bool,
+    /// Identificadores Unicode (UAX #31): empiezan con XID_Start, siguen con XID_Continue y se
+    /// normalizan a NFC. Advierte de identificadores que mezclan escrituras o se confunden con otros.
+    pub unicode_identifiers: bool,
+    /// Advertencias que se reportan en `tokenize_with_diagnostics`
+    #[serde(default)]
+    pub lints: LintOptions,
+    /// Tablas de palabras reservadas y símbolos a usar
+    #[serde(default)]
+    pub config: LexerConfig,
+    /// Unidad de las columnas de los tokens y errores que regresa `tokenize`
+    #[serde(default)]
+    pub position_encoding: PositionEncoding,
+    /// Codificación de los archivos y bytes que se tokenizan (`tokenize_file`, `tokenize_bytes`)
+    #[serde(default)]
+    pub encoding: SourceEncoding,
+}
+
+impl LexerOptions {
+    /// Opciones por defecto de cada dialecto
+    pub fn for_dialect(dialect: Dialect) -> Self {
+        LexerOptions {
+            c_operators: matches!(dialect, Dialect::CLike),
+            config: LexerConfig::new(dialect),
+            ..Default::default()
+        }
+    }
+
+    /// Perfil para código portado de C: acepta `&&` y `||`
+    pub fn c_like() -> Self {
+        LexerOptions::for_dialect(Dialect::CLike)
+    }
+}
+
+/// Longitud máxima de identificador que usa `LintOptions::all`
+pub const DEFAULT_MAX_IDENTIFIER_LENGTH: usize = 31;
+
+/// Advertencias léxicas, cada una se activa por separado. Por defecto están todas apagadas.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct LintOptions {
+    /// Identificadores con más caracteres que este límite
+    pub max_identifier_length: Option<usize>,
+    /// Palabras reservadas con otras mayúsculas (`WHILE`). Siempre activa con
+    /// `LexerOptions::case_insensitive_keywords`.
+    pub keyword_case: bool,
+    /// Indentación con tabuladores y espacios en la misma línea
+    pub mixed_indentation: bool,
+    /// Espacios o tabuladores al final de la línea
+    pub trailing_whitespace: bool,
+    /// Números con ceros a la izquierda (`007`)
+    pub leading_zeros: bool,
+    /// Comentarios cuyo contenido es código válido (`// x = 1;`)
+    pub commented_out_code: bool,
+}
+
+impl LintOptions {
+    /// Nombres de las advertencias para `enable`
+    pub const NAMES: [&'static str; 6] = [
+        "long-identifiers",
+        "keyword-case",
+        "mixed-indentation",
+        "trailing-whitespace",
+        "leading-zeros",
+        "commented-out-code",
+    ];
+
+    pub fn all() -> Self {
+        LintOptions {
+            max_identifier_length: Some(DEFAULT_MAX_IDENTIFIER_LENGTH),
+            keyword_case: true,
+            mixed_indentation: true,
+            trailing_whitespace: true,
+            leading_zeros: true,
+            commented_out_code: true,
+        }
+    }
+
+    /// Activa una advertencia por su nombre (ver `NAMES`) o todas con `all`
+    pub fn enable(&mut self, name: &str) -> Result<(), String> {
+        match name {
+            "all" => *self = LintOptions::all(),
+            "long-identifiers" => {
+                self.max_identifier_length = self
+                    .max_identifier_length
+                    .or(Some(DEFAULT_MAX_IDENTIFIER_LENGTH))
+            }
+            "keyword-case" => self.keyword_case = true,
+            "mixed-indentation" => self.mixed_indentation = true,
+            "trailing-whitespace" => self.trailing_whitespace = true,
+            "leading-zeros" => self.leading_zeros = true,
+            "commented-out-code" => self.commented_out_code = true,
+            _ => {
+                return Err(format!(
+                    "Advertencia '{}' desconocida, usa all o una de: {}",
+                    name,
+                    LintOptions::NAMES.join(", ")
+                ))
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Palabras reservadas y símbolos de un caracter que reconoce el scanner. Empieza con las tablas
+/// de un dialecto y se puede modificar en tiempo de ejecución, incluso con tipos de token propios
+/// (`TokenType::CUSTOM`).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(from = "ConfigTables")]
+pub struct LexerConfig {
+    /// Dialecto del que se tomaron las tablas
+    pub dialect: Dialect,
+    keywords: HashMap<String, TokenType>,
+    symbols: HashMap<char, TokenType>,
+    /// Palabras reservadas por su versión en minúsculas, para `keyword_ignore_case`. Si dos solo
+    /// difieren en mayúsculas se guarda la menor, para que no dependa del orden de inserción.
+    #[serde(skip_serializing)]
+    lowercase_keywords: HashMap<String, String>,
+}
+
+/// Lo que se serializa de `LexerConfig`; el índice en minúsculas se reconstruye al leerlo
+#[derive(Deserialize)]
+struct ConfigTables {
+    dialect: Dialect,
+    keywords: HashMap<String, TokenType>,
+    symbols: HashMap<char, TokenType>,
+}
+
+impl From<ConfigTables> for LexerConfig {
+    fn from(tables: ConfigTables) -> Self {
+        LexerConfig {
+            dialect: tables.dialect,
+            lowercase_keywords: lowercase_index(tables.keywords.keys()),
+            keywords: tables.keywords,
+            symbols: tables.symbols,
+        }
+    }
+}
+
+fn lowercase_index<'a>(words: impl Iterator<Item = &'a String>) -> HashMap<String, String> {
+    let mut index = HashMap::new();
+    for word in words {
+        index_keyword(&mut index, word);
+    }
+    index
+}
+
+fn index_keyword(index: &mut HashMap<String, String>, word: &str) {
+    index
+        .entry(word.to_ascii_lowercase())
+        .and_modify(|indexed: &mut String| {
+            if word < indexed.as_str() {
+                *indexed = word.to_string();
+            }
+        })
+        .or_insert_with(|| word.to_string());
+}
+
+impl LexerConfig {
+    pub fn new(dialect: Dialect) -> Self {
+        ConfigTables {
+            dialect,
+            keywords: dialect
+                .keywords()
+                .entries()
+                .map(|(word, token)| (word.to_string(), token.clone()))
+                .collect(),
+            symbols: dialect
+                .symbols()
+                .entries()
+                .filter_map(|(symbol, token)| Some((symbol.chars().next()?, token.clone())))
+                .collect(),
+        }
+        .into()
+    }
+
+    /// Cambia a las tablas de `dialect` conservando lo que se agregó, cambió o quitó sobre las del
+    /// dialecto actual
+    pub fn switch_dialect(&mut self, dialect: Dialect) {
+        let base = LexerConfig::new(self.dialect);
+        let mut config = LexerConfig::new(dialect);
+        for (word, token) in self.keywords() {
+            if base.keyword(word) != Some(token) {
+                config.keywords.insert(word.to_string(), token.clone());
+            }
+        }
+        for (word, _) in base
+            .keywords()
+            .filter(|(word, _)| self.keyword(word).is_none())
+        {
+            config.keywords.remove(word);
+        }
+        for (symbol, token) in self.symbols() {
+            if base.symbol(symbol) != Some(token) {
+                config.symbols.insert(symbol, token.clone());
+            }
+        }
+        for (symbol, _) in base
+            .symbols()
+            .filter(|(symbol, _)| self.symbol(*symbol).is_none())
+        {
+            config.symbols.remove(&symbol);
+        }
+        config.lowercase_keywords = lowercase_index(config.keywords.keys());
+        *self = config;
+    }
+
+    pub fn keyword(&self, word: &str) -> Option<&TokenType> {
+        self.keywords.get(word)
+    }
+
+    /// Busca una palabra reservada sin importar mayúsculas; regresa también como está escrita en la tabla
+    pub fn keyword_ignore_case(&self, word: &str) -> Option<(&str, &TokenType)> {
+        let keyword = self.lowercase_keywords.get(&word.to_ascii_lowercase())?;
+        self.keywords
+            .get_key_value(keyword)
+            .map(|(keyword, token)| (keyword.as_str(), token))
+    }
+
+    pub fn symbol(&self, symbol: char) -> Option<&TokenType> {
+        self.symbols.get(&symbol)
+    }
+
+    pub fn keywords(&self) -> impl Iterator<Item = (&str, &TokenType)> {
+        self.keywords
+            .iter()
+            .map(|(word, token)| (word.as_str(), token))
+    }
+
+    pub fn symbols(&self) -> impl Iterator<Item = (char, &TokenType)> {
+        self.symbols.iter().map(|(symbol, token)| (*symbol, token))
+    }
+
+    /// Agrega una palabra reservada o cambia el token de una existente. Acepta identificadores
+    /// Unicode (UAX #31), que solo se reconocen con `LexerOptions::unicode_identifiers`; se guardan
+    /// en forma NFC, que es como el scanner compara los identificadores.
+    pub fn add_keyword(&mut self, word: &str, token: TokenType) -> Result<(), String> {
+        let mut chars = word.chars();
+        let valid = chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c.is_xid_start())
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c.is_xid_continue());
+        if !valid {
+            return Err(format!(
+                "'{}' no puede ser palabra reservada porque no es un identificador",
+                word
+            ));
+        }
+        let word: String = word.nfc().collect();
+        index_keyword(&mut self.lowercase_keywords, &word);
+        self.keywords.insert(word, token);
+        Ok(())
+    }
+
+    /// Quita una palabra reservada, que pasa a leerse como `ID`
+    pub fn remove_keyword(&mut self, word: &str) -> Option<TokenType> {
+        let token = self.keywords.remove(word)?;
+        let lowercase = word.to_ascii_lowercase();
+        if self
+            .lowercase_keywords
+            .get(&lowercase)
+            .is_some_and(|w| w == word)
+        {
+            self.lowercase_keywords.remove(&lowercase);
+            // otra palabra que solo difiere en mayúsculas toma su lugar
+            let others = self
+                .keywords
+                .keys()
+                .filter(|other| other.eq_ignore_ascii_case(word));
+            for other in others {
+                index_keyword(&mut self.lowercase_keywords, other);
+            }
+        }
+        Some(token)
+    }
+
+    /// Agrega un símbolo de un caracter o cambia el token de uno existente. No se permiten
+    /// caracteres con los que empiezan otros tokens (letras, dígitos, espacios u operadores
+    /// como `+` o `<`).
+    pub fn add_symbol(&mut self, symbol: char, token: TokenType) -> Result<(), String> {
+        if symbol.is_whitespace()
+            || symbol.is_alphanumeric()
+            || ['_', '-', '+', '/', '!', '<', '>', '='].contains(&symbol)
+        {
+            return Err(format!(
+                "'{}' no puede ser un símbolo porque inicia otros tokens",
+                symbol
+            ));
+        }
+        self.symbols.insert(symbol, token);
+        Ok(())
+    }
+
+    /// Quita un símbolo, que pasa a ser un caracter no permitido
+    pub fn remove_symbol(&mut self, symbol: char) -> Option<TokenType> {
+        self.symbols.remove(&symbol)
+    }
+}
+
+impl Default for LexerConfig {
+    fn default() -> Self {
+        LexerConfig::new(Dialect::default())
+    }
+}
@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+use unicode_width::UnicodeWidthChar;
+
+use crate::{
+    data::{Cursor, DocComment, Error, TokenRef},
+    line_endings::lines_with_endings,
+};
+
+/// Ancho de tabulador por defecto para `PositionEncoding::Display`
+pub const DEFAULT_TAB_WIDTH: usize = 8;
+
+/// Unidad en la que se cuentan las columnas de `Cursor`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum PositionEncoding {
+    /// Caracteres Unicode (comportamiento original)
+    #[default]
+    Chars,
+    /// Bytes de UTF-8
+    Bytes,
+    /// Unidades de UTF-16, como las posiciones de LSP
+    Utf16,
+    /// Columnas de terminal: los tabuladores avanzan al siguiente múltiplo de `tab_width` y los
+    /// caracteres anchos ocupan dos columnas
+    Display { tab_width: usize },
+}
+
+impl PositionEncoding {
+    /// Columnas que avanza `c` si empieza en la columna `col` (contando desde 0)
+    pub(crate) fn width(&self, c: char, col: usize) -> usize {
+        match self {
+            PositionEncoding::Chars => 1,
+            PositionEncoding::Bytes => c.len_utf8(),
+            PositionEncoding::Utf16 => c.len_utf16(),
+            PositionEncoding::Display { tab_width } if c == '\t' => {
+                let tab_width = (*tab_width).max(1);
+                tab_width - col % tab_width
+            }
+            PositionEncoding::Display { .. } => c.width().unwrap_or(0),
+        }
+    }
+}
+
+/// Convierte cursores contados en caracteres a otra `PositionEncoding`
+pub struct ColumnMap<'a> {
+    lines: Vec<&'a str>,
+    encoding: PositionEncoding,
+}
+
+impl<'a> ColumnMap<'a> {
+    pub fn new(contents: &'a str, encoding: PositionEncoding) -> Self {
+        ColumnMap {
+            lines: lines_with_endings(contents)
+                .into_iter()
+                .map(|(line, _)| line)
+                .collect(),
+            encoding,
+        }
+    }
+
+    pub fn convert(&self, cursor: &Cursor) -> Cursor {
+        if self.encoding == PositionEncoding::Chars || cursor.col < 1 || cursor.lin < 1 {
+            return cursor.clone();
+        }
+        let line = self
+            .lines
+            .get(cursor.lin as usize - 1)
+            .copied()
+            .unwrap_or("");
+        let mut remaining = cursor.col as usize - 1;
+        let mut col = 0;
+        for c in line.chars().take(remaining) {
+            col += self.encoding.width(c, col);
+            remaining -= 1;
+        }
+        // posiciones después del final de la línea (el salto de línea o el fin del archivo)
+        Cursor {
+            col: (col + remaining) as i32 + 1,
+            lin: cursor.lin,
+        }
+    }
+
+    pub fn convert_token(&self, token: &mut TokenRef) {
+        token.start = self.convert(&token.start);
+        token.end = self.convert(&token.end);
+    }
+
+    pub fn convert_error(&self, error: &mut Error) {
+        error.start = self.convert(&error.start);
+        error.end = self.convert(&error.end);
+        if let Some(suggestion) = error.suggestion.as_mut() {
+            suggestion.start = self.convert(&suggestion.start);
+            suggestion.end = self.convert(&suggestion.end);
+        }
+    }
+
+    pub fn convert_doc(&self, doc: &mut DocComment) {
+        doc.start = self.convert(&doc.start);
+        doc.end = self.convert(&doc.end);
+    }
+}
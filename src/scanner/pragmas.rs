@@ -0,0 +1,118 @@
+use crate::{
+    codes,
+    data::{Cursor, Error, Severity, TokenRef},
+};
+
+/// Códigos a los que aplica un pragma
+#[derive(Debug, Clone, PartialEq)]
+pub enum Codes {
+    All,
+    Only(Vec<String>),
+}
+
+impl Codes {
+    pub fn matches(&self, code: &str) -> bool {
+        match self {
+            Codes::All => true,
+            Codes::Only(codes) => codes.iter().any(|c| c == code),
+        }
+    }
+
+    /// `(L0001, E0001)`; sin paréntesis, `()` o `(all)` aplican a todos
+    fn parse(arguments: &str) -> Option<Codes> {
+        let arguments = arguments.trim();
+        if arguments.is_empty() {
+            return Some(Codes::All);
+        }
+        let list = arguments.strip_prefix('(')?.strip_suffix(')')?;
+        let codes: Vec<String> = list
+            .split(',')
+            .map(|code| code.trim().to_string())
+            .filter(|code| !code.is_empty())
+            .collect();
+        if codes.is_empty() || codes.iter().any(|code| code == "all") {
+            Some(Codes::All)
+        } else {
+            Some(Codes::Only(codes))
+        }
+    }
+}
+
+/// Instrucción dentro de un comentario `// scanner: ...` o `/* scanner: ... */`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pragma {
+    /// `allow(L0001)`: suprime los diagnósticos de la siguiente línea
+    Allow(Codes),
+    /// `off` u `off(L0004)`: suprime los diagnósticos hasta el siguiente `on`
+    Off(Codes),
+    /// `on`: termina las regiones abiertas con `off`
+    On,
+}
+
+/// Regresa `None` si el comentario no es un pragma y `Some(Err(..))` si lo es pero no se entiende
+pub fn parse_pragma(comment: &str) -> Option<Result<Pragma, String>> {
+    let body = match comment.strip_prefix("//") {
+        Some(body) => body,
+        None => {
+            let body = comment.strip_prefix("/*")?;
+            body.strip_suffix("*/").unwrap_or(body)
+        }
+    };
+    let directive = body.trim().strip_prefix("scanner:")?.trim();
+    let (name, arguments) = directive
+        .find('(')
+        .map_or((directive, ""), |i| directive.split_at(i));
+    let pragma = match (name.trim(), Codes::parse(arguments)) {
+        ("allow", Some(codes)) => Ok(Pragma::Allow(codes)),
+        ("off", Some(codes)) => Ok(Pragma::Off(codes)),
+        ("on", Some(Codes::All)) => Ok(Pragma::On),
+        _ => Err(format!("Pragma '{}' desconocido", directive)),
+    };
+    Some(pragma)
+}
+
+/// Diagnósticos suprimidos por los pragmas de un archivo
+#[derive(Debug, Default)]
+pub struct Suppressions {
+    lines: Vec<(i32, Codes)>,
+    regions: Vec<(Cursor, Option<Cursor>, Codes)>,
+}
+
+impl Suppressions {
+    /// Aplica el pragma del comentario, si tiene uno. Regresa una advertencia si no se entiende.
+    pub fn add_comment(&mut self, comment: &TokenRef) -> Option<Error> {
+        match parse_pragma(&comment.lexemme)? {
+            Ok(Pragma::Allow(codes)) => self.lines.push((comment.end.lin + 1, codes)),
+            Ok(Pragma::Off(codes)) => self.regions.push((comment.end.clone(), None, codes)),
+            Ok(Pragma::On) => {
+                for region in self.regions.iter_mut().filter(|r| r.1.is_none()) {
+                    region.1 = Some(comment.start.clone());
+                }
+            }
+            Err(message) => {
+                return Some(Error {
+                    start: comment.start.clone(),
+                    end: comment.end.clone(),
+                    message,
+                    lexemme: comment.lexemme.to_string(),
+                    suggestion: None,
+                    severity: Severity::Warning,
+                    code: codes::UNKNOWN_PRAGMA.to_string(),
+                })
+            }
+        }
+        None
+    }
+
+    pub fn is_suppressed(&self, diagnostic: &Error) -> bool {
+        let position = (diagnostic.start.lin, diagnostic.start.col);
+        self.lines
+            .iter()
+            .any(|(lin, codes)| *lin == diagnostic.start.lin && codes.matches(&diagnostic.code))
+            || self.regions.iter().any(|(start, end, codes)| {
+                (start.lin, start.col) <= position
+                    && end.as_ref().is_none_or(|end| position < (end.lin, end.col))
+                    && codes.matches(&diagnostic.code)
+            })
+    }
+}
@@ -0,0 +1,394 @@
+use std::{collections::VecDeque, io::Read};
+
+use crate::{
+    codes,
+    data::{Cursor, Error, Severity, Token, TokenType},
+    directives::header_options,
+    encoding::{decode, without_replaced_symbols, SourceEncoding},
+    get_token_with,
+    line_endings::BOM,
+    options::LexerOptions,
+    positions::{ColumnMap, PositionEncoding},
+    pragmas::Suppressions,
+    utils::{advance_line_break, init_cursor, unknown_symbols_error},
+    with_suggestion,
+};
+
+/// Tamaño por defecto de cada lectura
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Scanner sobre un `io::Read` que solo guarda en memoria el texto que aún no se tokeniza.
+///
+/// Produce los mismos tokens y errores que `tokenize_with` (incluida la directiva de cabecera, los
+/// pragmas de los comentarios, los errores de UTF-8 inválido y las columnas en
+/// `options.position_encoding`), en orden. De las advertencias solo guarda las de la directiva de
+/// cabecera (ver `warnings`) y no reporta documentación. Un token que cruza el final del buffer se
+/// vuelve a leer con más texto, así que la memoria crece solo con el token más largo.
+pub struct ReaderLexer<R: Read> {
+    reader: R,
+    options: LexerOptions,
+    chunk_size: usize,
+    /// Texto decodificado; lo anterior a `consumed` ya se tokenizó
+    buffer: String,
+    consumed: usize,
+    /// Bytes de una secuencia UTF-8 incompleta al final de la última lectura
+    pending: Vec<u8>,
+    exhausted: bool,
+    /// Ya se quitó (o no venía) el BOM del inicio
+    bom_checked: bool,
+    started: bool,
+    done: bool,
+    /// Posición del inicio del texto sin tokenizar, en caracteres, y su columna en
+    /// `options.position_encoding` contando desde 0
+    cursor: Cursor,
+    cursor_units: usize,
+    /// Posición del final del buffer, para ubicar las secuencias inválidas
+    end: Cursor,
+    end_units: usize,
+    after_cr: bool,
+    prev: Option<TokenType>,
+    last_error: Option<Error>,
+    /// Tokens y errores ya convertidos a `options.position_encoding`, con su inicio en caracteres
+    queue: VecDeque<(Cursor, Result<Token, Error>)>,
+    /// Errores de codificación en caracteres y convertidos; se filtran con los pragmas al salir
+    encoding_errors: VecDeque<(Error, Error)>,
+    /// Pragmas de los comentarios ya escaneados; solo suprimen errores posteriores a ellos
+    suppressions: Suppressions,
+    warnings: Vec<Error>,
+}
+
+impl<R: Read> ReaderLexer<R> {
+    pub fn new(reader: R, options: &LexerOptions) -> Self {
+        ReaderLexer::with_chunk_size(reader, options, DEFAULT_CHUNK_SIZE)
+    }
+
+    pub fn with_chunk_size(reader: R, options: &LexerOptions, chunk_size: usize) -> Self {
+        ReaderLexer {
+            reader,
+            options: options.clone(),
+            chunk_size: chunk_size.max(1),
+            buffer: String::new(),
+            consumed: 0,
+            pending: Vec::new(),
+            exhausted: false,
+            bom_checked: false,
+            started: false,
+            done: false,
+            cursor: init_cursor(),
+            cursor_units: 0,
+            end: init_cursor(),
+            end_units: 0,
+            after_cr: false,
+            prev: None,
+            last_error: None,
+            queue: VecDeque::new(),
+            encoding_errors: VecDeque::new(),
+            suppressions: Suppressions::default(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Advertencias de la directiva de cabecera; se conocen después de pedir el primer token
+    pub fn warnings(&self) -> &[Error] {
+        &self.warnings
+    }
+
+    /// Lee más texto. Cada lectura es al menos tan grande como lo que ya hay en el buffer para que
+    /// volver a escanear un token largo no cueste más que leerlo.
+    fn refill(&mut self) {
+        self.buffer.drain(..self.consumed);
+        self.consumed = 0;
+        let mut bytes = std::mem::take(&mut self.pending);
+        let start = bytes.len();
+        bytes.resize(start + self.chunk_size.max(self.buffer.len()), 0);
+        let read = loop {
+            match self.reader.read(&mut bytes[start..]) {
+                Ok(read) => break read,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    let end = self.end_position();
+                    let error = Error {
+                        start: end.clone(),
+                        end,
+                        message: format!("No se pudo leer la entrada: {}", e),
+                        lexemme: String::new(),
+                        suggestion: None,
+                        severity: Severity::Error,
+                        code: codes::UNEXPECTED.to_string(),
+                    };
+                    self.queue.push_back((self.end.clone(), Err(error)));
+                    break 0;
+                }
+            }
+        };
+        bytes.truncate(start + read);
+        if read == 0 {
+            self.exhausted = true;
+        }
+        match self.options.encoding {
+            SourceEncoding::Utf8 => self.push_utf8(&bytes),
+            encoding => self.push_text(&decode(&bytes, encoding).text),
+        }
+    }
+
+    fn push_utf8(&mut self, mut bytes: &[u8]) {
+        while !bytes.is_empty() {
+            match std::str::from_utf8(bytes) {
+                Ok(text) => {
+                    self.push_text(text);
+                    return;
+                }
+                Err(e) => {
+                    let (valid, rest) = bytes.split_at(e.valid_up_to());
+                    // from_utf8 ya validó este prefijo
+                    self.push_text(std::str::from_utf8(valid).unwrap_or_default());
+                    let invalid = match e.error_len() {
+                        Some(len) => &rest[..len],
+                        None if !self.exhausted => {
+                            // la secuencia puede completarse con la siguiente lectura
+                            self.pending = rest.to_vec();
+                            return;
+                        }
+                        None => rest,
+                    };
+                    let hex: Vec<String> = invalid.iter().map(|b| format!("0x{:02X}", b)).collect();
+                    let error = Error {
+                        start: self.end.clone(),
+                        end: Cursor {
+                            col: self.end.col + 1,
+                            lin: self.end.lin,
+                        },
+                        message: format!("Secuencia UTF-8 inválida: {}", hex.join(" ")),
+                        lexemme: char::REPLACEMENT_CHARACTER.to_string(),
+                        suggestion: None,
+                        severity: Severity::Error,
+                        code: codes::INVALID_UTF8.to_string(),
+                    };
+                    let start = self.end_position();
+                    self.push_text(&char::REPLACEMENT_CHARACTER.to_string());
+                    let converted = Error {
+                        start,
+                        end: self.end_position(),
+                        ..error.clone()
+                    };
+                    self.encoding_errors.push_back((error, converted));
+                    bytes = &rest[invalid.len()..];
+                }
+            }
+        }
+    }
+
+    fn push_text(&mut self, mut text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        if !self.bom_checked {
+            // solo el primer BOM no es parte del texto ni cuenta como columna
+            text = text.strip_prefix(BOM).unwrap_or(text);
+            self.bom_checked = true;
+        }
+        for c in text.chars() {
+            match c {
+                '\n' if self.after_cr => {}
+                '\r' | '\n' => {
+                    self.end.lin += 1;
+                    self.end.col = 1;
+                    self.end_units = 0;
+                }
+                _ => {
+                    self.end.col += 1;
+                    self.end_units += self.options.position_encoding.width(c, self.end_units);
+                }
+            }
+            self.after_cr = c == '\r';
+        }
+        self.buffer.push_str(text);
+    }
+
+    /// `end` en `options.position_encoding`
+    fn end_position(&self) -> Cursor {
+        Cursor {
+            col: self.end_units as i32 + 1,
+            lin: self.end.lin,
+        }
+    }
+
+    /// Lee hasta tener la primera línea completa y aplica su directiva de cabecera
+    fn start(&mut self) {
+        while !self.exhausted && !self.buffer.contains(['\r', '\n']) {
+            self.refill();
+        }
+        let (options, mut warnings) = header_options(&self.buffer, &self.options);
+        let columns = ColumnMap::new(&self.buffer, options.position_encoding);
+        warnings
+            .iter_mut()
+            .for_each(|warning| columns.convert_error(warning));
+        self.options = options;
+        self.warnings = warnings;
+        self.started = true;
+    }
+
+    /// Escanea el siguiente token y deja en la cola lo que produzca
+    fn scan_next(&mut self) {
+        if !self.started {
+            self.start();
+        }
+        loop {
+            let text = &self.buffer[self.consumed..];
+            let mut cursor = self.cursor.clone();
+            let (result, rest) =
+                get_token_with(text, &mut cursor, &self.options, self.prev.as_ref());
+            if rest.is_empty() && !self.exhausted {
+                // el token (o su lookahead) puede seguir en la siguiente lectura
+                self.refill();
+                continue;
+            }
+            let used = text.len() - rest.len();
+            match result {
+                Ok(token) => match token.token_type {
+                    TokenType::EOF => self.done = true,
+                    TokenType::BLOCK_COMMENT
+                    | TokenType::INLINE_COMMENT
+                    | TokenType::DOC_COMMENT => {
+                        // un pragma mal escrito es una advertencia, y aquí no se reportan
+                        let _ = self.suppressions.add_comment(&token.as_token_ref());
+                    }
+                    TokenType::NONE => {
+                        let error = self
+                            .without_replaced_symbols(unknown_symbols_error(&token))
+                            .map(|error| with_suggestion(self.last_error.as_ref(), error, rest));
+                        self.prev = Some(token.token_type.clone());
+                        self.push_token(token);
+                        if let Some(error) = error {
+                            self.push_error(error);
+                        }
+                    }
+                    _ => {
+                        self.prev = Some(token.token_type.clone());
+                        self.push_token(token);
+                    }
+                },
+                Err(error) => {
+                    if let Some(error) = self.without_replaced_symbols(error) {
+                        let error = with_suggestion(self.last_error.as_ref(), error, rest);
+                        self.push_error(error);
+                    }
+                }
+            }
+            let text = &self.buffer[self.consumed..];
+            self.cursor_units = self.convert(text, &cursor).col as usize - 1;
+            self.consumed += used;
+            self.cursor = cursor;
+            return;
+        }
+    }
+
+    /// Columna de `target` en `options.position_encoding`. `text` empieza en `cursor` y
+    /// `target` está dentro de él o justo después.
+    fn convert(&self, text: &str, target: &Cursor) -> Cursor {
+        let encoding = self.options.position_encoding;
+        if encoding == PositionEncoding::Chars {
+            return target.clone();
+        }
+        let mut cursor = self.cursor.clone();
+        let mut units = self.cursor_units;
+        for (i, c) in text.char_indices() {
+            if (cursor.lin, cursor.col) >= (target.lin, target.col) {
+                break;
+            }
+            let (lin, col) = (cursor.lin, cursor.col);
+            cursor.col += 1;
+            advance_line_break(c, &text[i..], &mut cursor);
+            if cursor.lin != lin {
+                units = 0;
+            } else if cursor.col != col {
+                units += encoding.width(c, units);
+            }
+        }
+        if cursor.lin != target.lin {
+            return target.clone();
+        }
+        // lo que quede después del texto cuenta como una columna por caracter
+        Cursor {
+            col: (units as i32) + 1 + (target.col - cursor.col),
+            lin: target.lin,
+        }
+    }
+
+    fn push_token(&mut self, mut token: Token) {
+        let start = token.start.clone();
+        let text = &self.buffer[self.consumed..];
+        token.start = self.convert(text, &token.start);
+        token.end = self.convert(text, &token.end);
+        self.queue.push_back((start, Ok(token)));
+    }
+
+    /// El U+FFFD de una secuencia inválida ya se reporta en `encoding_errors`
+    fn without_replaced_symbols(&self, error: Error) -> Option<Error> {
+        let replaced = |cursor: &Cursor| {
+            self.encoding_errors
+                .iter()
+                .any(|(error, _)| error.start == *cursor)
+        };
+        without_replaced_symbols(error, &replaced)
+    }
+
+    /// Los comentarios anteriores al error ya se escanearon, así que sus pragmas ya se conocen
+    fn push_error(&mut self, mut error: Error) {
+        self.last_error = Some(error.clone());
+        if self.suppressions.is_suppressed(&error) {
+            return;
+        }
+        let start = error.start.clone();
+        let text = &self.buffer[self.consumed..];
+        error.start = self.convert(text, &error.start);
+        error.end = self.convert(text, &error.end);
+        if let Some(suggestion) = error.suggestion.as_mut() {
+            suggestion.start = self.convert(text, &suggestion.start);
+            suggestion.end = self.convert(text, &suggestion.end);
+        }
+        self.queue.push_back((start, Err(error)));
+    }
+}
+
+impl<R: Read> Iterator for ReaderLexer<R> {
+    type Item = Result<Token, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            while self.queue.is_empty() && !self.done {
+                self.scan_next();
+            }
+            // los errores de codificación van en orden con lo demás; cuando salen ya se
+            // escanearon los comentarios anteriores a ellos
+            let encoding_first = match (self.encoding_errors.front(), self.queue.front()) {
+                (Some((error, _)), Some((start, _))) => {
+                    (error.start.lin, error.start.col) <= (start.lin, start.col)
+                }
+                (Some(_), None) => true,
+                (None, _) => false,
+            };
+            if !encoding_first {
+                let (_, item) = self.queue.pop_front()?;
+                return Some(item);
+            }
+            let (error, converted) = self.encoding_errors.pop_front()?;
+            if !self.suppressions.is_suppressed(&error) {
+                return Some(Err(converted));
+            }
+        }
+    }
+}
+
+/// Tokeniza todo lo que se lea de `reader` con memoria acotada (ver `ReaderLexer`)
+pub fn tokenize_reader<R: Read>(reader: R, options: &LexerOptions) -> (Vec<Token>, Vec<Error>) {
+    let mut tokens = Vec::new();
+    let mut errors = Vec::new();
+    for item in ReaderLexer::new(reader, options) {
+        match item {
+            Ok(token) => tokens.push(token),
+            Err(error) => errors.push(error),
+        }
+    }
+    (tokens, errors)
+}
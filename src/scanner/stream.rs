@@ -0,0 +1,199 @@
+use crate::{
+    codes,
+    data::{Cursor, Error, Severity, Token, TokenType},
+    directives::header_options,
+    get_token_with,
+    line_endings::BOM,
+    options::LexerOptions,
+    positions::{ColumnMap, PositionEncoding},
+    pragmas::Suppressions,
+    utils::{init_cursor, unknown_symbols_error},
+    with_suggestion,
+};
+
+/// Posición guardada con `TokenStream::mark` para regresar a ella con `reset`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mark(usize);
+
+/// Tokens con lookahead para escribir un parser. Escanea el texto conforme se piden tokens, salta
+/// los comentarios y guarda los tokens ya leídos para poder regresar a una `Mark`. Los errores
+/// léxicos que no suprimen los pragmas se acumulan en `errors` y las advertencias de la directiva de
+/// cabecera en `warnings`. Las posiciones se cuentan en `options.position_encoding`.
+pub struct TokenStream<'a> {
+    rest: &'a str,
+    options: LexerOptions,
+    /// Posición en caracteres; los tokens y errores guardados ya están convertidos
+    cursor: Cursor,
+    /// Solo existe si `options.position_encoding` no cuenta caracteres
+    columns: Option<ColumnMap<'a>>,
+    tokens: Vec<Token>,
+    position: usize,
+    errors: Vec<Error>,
+    /// Último error encontrado, aunque se haya suprimido, para no repetir sugerencias
+    last_error: Option<Error>,
+    suppressions: Suppressions,
+    warnings: Vec<Error>,
+    done: bool,
+}
+
+impl<'a> TokenStream<'a> {
+    pub fn new(contents: &'a str, options: &LexerOptions) -> Self {
+        let contents = contents.strip_prefix(BOM).unwrap_or(contents);
+        let (options, mut warnings) = header_options(contents, options);
+        let columns = (options.position_encoding != PositionEncoding::Chars)
+            .then(|| ColumnMap::new(contents, options.position_encoding));
+        if let Some(columns) = columns.as_ref() {
+            warnings
+                .iter_mut()
+                .for_each(|warning| columns.convert_error(warning));
+        }
+        TokenStream {
+            rest: contents,
+            options,
+            cursor: init_cursor(),
+            columns,
+            tokens: Vec::new(),
+            position: 0,
+            errors: Vec::new(),
+            last_error: None,
+            suppressions: Suppressions::default(),
+            warnings,
+            done: false,
+        }
+    }
+
+    /// Recorre tokens ya escaneados, por ejemplo los de `tokenize_with`
+    pub fn from_tokens(tokens: Vec<Token>) -> Self {
+        let tokens: Vec<Token> = tokens.into_iter().filter(|t| !is_trivia(t)).collect();
+        TokenStream {
+            rest: "",
+            options: LexerOptions::default(),
+            cursor: tokens.last().map_or_else(init_cursor, |t| t.end.clone()),
+            columns: None,
+            tokens,
+            position: 0,
+            errors: Vec::new(),
+            last_error: None,
+            suppressions: Suppressions::default(),
+            warnings: Vec::new(),
+            done: true,
+        }
+    }
+
+    /// El token `n` posiciones adelante sin consumirlo; `peek(0)` es el siguiente
+    pub fn peek(&mut self, n: usize) -> Option<&Token> {
+        self.fill(self.position + n);
+        self.tokens.get(self.position + n)
+    }
+
+    /// Consume el siguiente token si es de tipo `token_type`; si no, regresa un error en la
+    /// posición actual sin consumir nada
+    pub fn expect(&mut self, token_type: TokenType) -> Result<Token, Box<Error>> {
+        let cursor = self.cursor();
+        match self.peek(0) {
+            Some(token) if token.token_type == token_type => Ok(self.next().unwrap()),
+            found => Err(Box::new(Error {
+                start: cursor.clone(),
+                end: found.map_or(cursor, |token| token.end.clone()),
+                message: match found {
+                    Some(token) => format!(
+                        "Se esperaba {:?} pero se encontró {:?} '{}'",
+                        token_type, token.token_type, token.lexemme
+                    ),
+                    None => format!("Se esperaba {:?} pero terminó el archivo", token_type),
+                },
+                lexemme: found.map_or(String::new(), |token| token.lexemme.clone()),
+                suggestion: None,
+                severity: Severity::Error,
+                code: codes::UNEXPECTED_TOKEN.to_string(),
+            })),
+        }
+    }
+
+    pub fn mark(&self) -> Mark {
+        Mark(self.position)
+    }
+
+    /// Regresa a una posición guardada con `mark`
+    pub fn reset(&mut self, mark: Mark) {
+        self.position = mark.0;
+    }
+
+    /// Inicio del siguiente token, o el final del texto si ya no hay más
+    pub fn cursor(&mut self) -> Cursor {
+        match self.peek(0) {
+            Some(token) => token.start.clone(),
+            None => match self.columns.as_ref() {
+                Some(columns) => columns.convert(&self.cursor),
+                None => self.cursor.clone(),
+            },
+        }
+    }
+
+    /// Errores léxicos del texto escaneado hasta ahora
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+
+    /// Advertencias de la directiva de cabecera
+    pub fn warnings(&self) -> &[Error] {
+        &self.warnings
+    }
+
+    /// Escanea hasta tener el token `index` o terminar el texto
+    fn fill(&mut self, index: usize) {
+        while self.tokens.len() <= index && !self.done {
+            let prev = self.tokens.last().map(|t| &t.token_type);
+            let (result, rest) = get_token_with(self.rest, &mut self.cursor, &self.options, prev);
+            self.rest = rest;
+            match result {
+                Ok(token) if token.token_type == TokenType::EOF => self.done = true,
+                Ok(token) if is_trivia(&token) => {
+                    // un pragma mal escrito es una advertencia, y aquí no se reportan
+                    let _ = self.suppressions.add_comment(&token.as_token_ref());
+                }
+                Ok(mut token) => {
+                    if matches!(token.token_type, TokenType::NONE) {
+                        self.push_error(unknown_symbols_error(&token), rest);
+                    }
+                    if let Some(columns) = self.columns.as_ref() {
+                        token.start = columns.convert(&token.start);
+                        token.end = columns.convert(&token.end);
+                    }
+                    self.tokens.push(token);
+                }
+                Err(error) => self.push_error(error, rest),
+            }
+        }
+    }
+
+    /// Los comentarios anteriores al error ya se escanearon, así que sus pragmas ya se conocen.
+    /// Los pragmas y las sugerencias se comparan en caracteres y el error se convierte al final.
+    fn push_error(&mut self, error: Error, rest: &str) {
+        let mut error = with_suggestion(self.last_error.as_ref(), error, rest);
+        self.last_error = Some(error.clone());
+        if !self.suppressions.is_suppressed(&error) {
+            if let Some(columns) = self.columns.as_ref() {
+                columns.convert_error(&mut error);
+            }
+            self.errors.push(error);
+        }
+    }
+}
+
+impl Iterator for TokenStream<'_> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek(0).cloned()?;
+        self.position += 1;
+        Some(token)
+    }
+}
+
+fn is_trivia(token: &Token) -> bool {
+    matches!(
+        token.token_type,
+        TokenType::INLINE_COMMENT | TokenType::BLOCK_COMMENT | TokenType::DOC_COMMENT
+    )
+}
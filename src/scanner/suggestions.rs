@@ -0,0 +1,131 @@
+use phf::phf_map;
+
+use crate::{
+    codes,
+    data::{Cursor, Error, Severity, Suggestion, Token, TokenType, KEYWORDS},
+    options::LexerConfig,
+};
+
+/// Operadores de otros lenguajes que nuestros alumnos suelen escribir, con su equivalente en Vanilla
+pub static OPERATOR_SUGGESTIONS: phf::Map<&'static str, &'static str> = phf_map! {
+    "&&" => "and",
+    "||" => "or",
+    ":=" => "=",
+};
+
+/// Palabras de otros dialectos que en Vanilla son identificadores, con la palabra reservada equivalente
+pub static KEYWORD_SUGGESTIONS: phf::Map<&'static str, &'static str> = phf_map! {
+    "real" => "double",
+    "float" => "double",
+    "int" => "integer",
+    "cin" => "stdin",
+    "cout" => "stdout",
+};
+
+/// Propone una corrección para un error léxico. `rest` es el texto que sigue al error, necesario
+/// para reconocer operadores de dos caracteres cuando solo el primero fue reportado.
+pub fn suggest(error: &Error, rest: &str) -> Option<Suggestion> {
+    let lexemme = error.lexemme.as_str();
+    if let Some(int_part) = lexemme.strip_suffix('.') {
+        if !int_part.is_empty() && int_part.chars().all(|c| c.is_ascii_digit()) {
+            return Some(Suggestion {
+                message: format!("Quizás quisiste escribir '{}0'", lexemme),
+                replacement: format!("{}0", lexemme),
+                start: error.start.clone(),
+                end: error.end.clone(),
+            });
+        }
+    }
+    if let Some(replacement) = OPERATOR_SUGGESTIONS.get(lexemme) {
+        return Some(operator_suggestion(
+            lexemme,
+            replacement,
+            error.start.clone(),
+            error.end.clone(),
+        ));
+    }
+    let mut chars = lexemme.chars();
+    if let (Some(first), None, Some(second)) = (chars.next(), chars.next(), rest.chars().next()) {
+        let operator = format!("{}{}", first, second);
+        if let Some(replacement) = OPERATOR_SUGGESTIONS.get(operator.as_str()) {
+            let mut end = error.end.clone();
+            end.col += 1;
+            return Some(operator_suggestion(
+                &operator,
+                replacement,
+                error.start.clone(),
+                end,
+            ));
+        }
+    }
+    None
+}
+
+/// Propone la palabra reservada de `config` para identificadores como `real` o `cin`: la de
+/// `KEYWORD_SUGGESTIONS` si está en la tabla, o si no la que produce el mismo token (`float` en
+/// c-like). El scanner no puede saber si son un error (son identificadores válidos); está pensado
+/// para que el parser lo use cuando espera un tipo u objeto.
+pub fn suggest_keyword(token: &Token, config: &LexerConfig) -> Option<Suggestion> {
+    if !matches!(token.token_type, TokenType::ID) {
+        return None;
+    }
+    let vanilla = KEYWORD_SUGGESTIONS.get(token.lexemme.as_str())?;
+    let token_type = KEYWORDS.get(vanilla)?;
+    let replacement = if config.keyword(vanilla) == Some(token_type) {
+        vanilla
+    } else {
+        config
+            .keywords()
+            .filter(|(_, keyword_type)| *keyword_type == token_type)
+            .map(|(keyword, _)| keyword)
+            .min()?
+    };
+    Some(Suggestion {
+        message: format!("Quizás quisiste escribir '{}'", replacement),
+        replacement: replacement.to_string(),
+        start: token.start.clone(),
+        end: token.end.clone(),
+    })
+}
+
+/// Advertencia con la sugerencia de `suggest_keyword`, que `tokenize_with_diagnostics` reporta
+/// para cada identificador que en otros dialectos es palabra reservada
+pub fn keyword_warning(token: &Token, config: &LexerConfig) -> Option<Error> {
+    let suggestion = suggest_keyword(token, config)?;
+    Some(Error {
+        start: token.start.clone(),
+        end: token.end.clone(),
+        message: format!(
+            "'{}' no es palabra reservada en {}, es un identificador",
+            token.lexemme, config.dialect
+        ),
+        lexemme: token.lexemme.clone(),
+        suggestion: Some(suggestion),
+        severity: Severity::Warning,
+        code: codes::FOREIGN_KEYWORD.to_string(),
+    })
+}
+
+/// Indica si `cursor` cae dentro del texto que reemplaza la sugerencia
+pub fn covers(suggestion: &Suggestion, cursor: &Cursor) -> bool {
+    let position = (cursor.lin, cursor.col);
+    (suggestion.start.lin, suggestion.start.col) <= position
+        && position < (suggestion.end.lin, suggestion.end.col)
+}
+
+fn operator_suggestion(
+    operator: &str,
+    replacement: &str,
+    start: Cursor,
+    end: Cursor,
+) -> Suggestion {
+    Suggestion {
+        message: format!(
+            "'{}' no existe en Vanilla, quizás quisiste escribir '{}'",
+            operator, replacement
+        ),
+        replacement: replacement.to_string(),
+        start,
+        end,
+    }
+}
@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::{Cursor, Token, TokenType};
+
+/// Identificador internado; se compara como un entero y se resuelve con el `Interner` que lo creó
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Token con el símbolo de su lexema si es un `ID`. El texto de un `ID` solo se guarda una vez, en
+/// el `Interner`; los demás tokens conservan su lexema.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InternedToken {
+    pub token_type: TokenType,
+    /// `None` en los `ID`
+    pub lexemme: Option<String>,
+    pub symbol: Option<Symbol>,
+    pub start: Cursor,
+    pub end: Cursor,
+}
+
+impl InternedToken {
+    /// Lexema del token; `None` si es un `ID` de otro `Interner`
+    pub fn lexemme<'a>(&'a self, interner: &'a Interner) -> Option<&'a str> {
+        match (&self.lexemme, self.symbol) {
+            (Some(lexemme), _) => Some(lexemme),
+            (None, Some(symbol)) => interner.resolve(symbol),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Tabla de identificadores de una sesión. Se puede usar con varios archivos para que el mismo
+/// nombre tenga el mismo `Symbol` en todos, y lleva la cuenta de cuántas veces aparece cada uno.
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    symbols: HashMap<String, Symbol>,
+    names: Vec<String>,
+    counts: Vec<usize>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Interner::default()
+    }
+
+    /// Símbolo de `name`, creándolo si no existe. No cuenta como una aparición.
+    pub fn intern(&mut self, name: &str) -> Symbol {
+        if let Some(symbol) = self.symbols.get(name) {
+            return *symbol;
+        }
+        let symbol = Symbol(self.names.len() as u32);
+        self.symbols.insert(name.to_string(), symbol);
+        self.names.push(name.to_string());
+        self.counts.push(0);
+        symbol
+    }
+
+    pub fn get(&self, name: &str) -> Option<Symbol> {
+        self.symbols.get(name).copied()
+    }
+
+    /// Texto del símbolo; `None` si el símbolo es de otro `Interner`
+    pub fn resolve(&self, symbol: Symbol) -> Option<&str> {
+        self.names.get(symbol.index()).map(String::as_str)
+    }
+
+    /// Veces que apareció el símbolo en los tokens internados; `None` si es de otro `Interner`
+    pub fn count(&self, symbol: Symbol) -> Option<usize> {
+        self.counts.get(symbol.index()).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Todos los identificadores con sus apariciones, en el orden en que se internaron
+    pub fn occurrences(&self) -> impl Iterator<Item = (Symbol, &str, usize)> {
+        self.names
+            .iter()
+            .zip(self.counts.iter())
+            .enumerate()
+            .map(|(index, (name, count))| (Symbol(index as u32), name.as_str(), *count))
+    }
+
+    /// Interna el lexema de cada `ID` y cuenta su aparición
+    pub fn intern_tokens(&mut self, tokens: Vec<Token>) -> Vec<InternedToken> {
+        tokens
+            .into_iter()
+            .map(|token| {
+                let (lexemme, symbol) = if matches!(token.token_type, TokenType::ID) {
+                    let symbol = self.intern(&token.lexemme);
+                    self.counts[symbol.index()] += 1;
+                    (None, Some(symbol))
+                } else {
+                    (Some(token.lexemme), None)
+                };
+                InternedToken {
+                    token_type: token.token_type,
+                    lexemme,
+                    symbol,
+                    start: token.start,
+                    end: token.end,
+                }
+            })
+            .collect()
+    }
+}
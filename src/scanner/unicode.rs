@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+
+use unicode_security::{skeleton, MixedScript};
+
+use crate::{
+    codes,
+    data::{Error, Severity, Token, TokenType},
+};
+
+/// Advertencia para identificadores que mezclan sistemas de escritura (`pаypal` con una 'а' cirílica)
+pub fn mixed_script_warning(token: &Token) -> Option<Error> {
+    if !matches!(token.token_type, TokenType::ID)
+        || token.lexemme.is_ascii()
+        || token.lexemme.as_str().is_single_script()
+    {
+        return None;
+    }
+    Some(Error {
+        start: token.start.clone(),
+        end: token.end.clone(),
+        message: format!(
+            "El identificador '{}' mezcla distintos sistemas de escritura",
+            token.lexemme
+        ),
+        lexemme: token.lexemme.clone(),
+        suggestion: None,
+        severity: Severity::Warning,
+        code: codes::MIXED_SCRIPT.to_string(),
+    })
+}
+
+/// Detecta identificadores distintos que se ven iguales (UTS #39), como `a` latina y `а` cirílica.
+/// Guarda el primer identificador visto con cada esqueleto.
+#[derive(Default)]
+pub struct ConfusableIdentifiers {
+    seen: HashMap<String, Token>,
+}
+
+impl ConfusableIdentifiers {
+    pub fn check(&mut self, token: &Token) -> Option<Error> {
+        if !matches!(token.token_type, TokenType::ID) {
+            return None;
+        }
+        let key: String = skeleton(&token.lexemme).collect();
+        let first = self.seen.entry(key).or_insert_with(|| token.clone());
+        if first.lexemme == token.lexemme || (first.lexemme.is_ascii() && token.lexemme.is_ascii())
+        {
+            return None;
+        }
+        Some(Error {
+            start: token.start.clone(),
+            end: token.end.clone(),
+            message: format!(
+                "El identificador '{}' se confunde con '{}' (línea {}, columna {})",
+                token.lexemme, first.lexemme, first.start.lin, first.start.col
+            ),
+            lexemme: token.lexemme.clone(),
+            suggestion: None,
+            severity: Severity::Warning,
+            code: codes::CONFUSABLE_IDENTIFIER.to_string(),
+        })
+    }
+}
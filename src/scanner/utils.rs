@@ -1,6 +1,14 @@
 use core::str;
+use std::sync::LazyLock;
 
-use crate::data::{Cursor, Error, TokenType, KEYWORDS};
+use unicode_xid::UnicodeXID;
+
+use crate::{
+    codes,
+    data::{Cursor, Error, Severity, Suggestion, Token, TokenRef, TokenType},
+    dialect::Dialect,
+    options::{LexerConfig, LexerOptions},
+};
 
 pub fn fake_cursor() -> Cursor {
     Cursor { col: 1, lin: 1 }
@@ -16,9 +24,155 @@ pub fn fake_error() -> Error {
         end: fake_cursor(),
         message: "Fake Error".to_string(),
         lexemme: "fake".to_string(),
+        suggestion: None,
+        severity: Severity::Error,
+        code: codes::UNEXPECTED.to_string(),
     }
 }
 
+/// Tablas por defecto, las mismas de `LexerOptions::default()`
+static DEFAULT_CONFIG: LazyLock<LexerConfig> = LazyLock::new(LexerConfig::default);
+
+/// Busca una palabra reservada en la configuración por defecto
 pub fn reserved_lookup(id: &str) -> TokenType {
-    KEYWORDS.get(id).cloned().unwrap_or(TokenType::ID)
+    reserved_lookup_with(id, &DEFAULT_CONFIG)
+}
+
+/// Busca una palabra reservada en las tablas de `dialect`; construye su configuración en cada
+/// llamada, para buscar muchas palabras conviene `reserved_lookup_with`
+pub fn reserved_lookup_in(id: &str, dialect: Dialect) -> TokenType {
+    reserved_lookup_with(id, &LexerConfig::new(dialect))
+}
+
+/// Busca una palabra reservada en `config`
+pub fn reserved_lookup_with(id: &str, config: &LexerConfig) -> TokenType {
+    config.keyword(id).cloned().unwrap_or(TokenType::ID)
+}
+
+/// Indica si un token puede ser el último de una expresión (un operando o un `)`).
+/// Despues de cualquier otro token un `+`/`-` solo puede ser un signo.
+pub fn can_end_expression(token_type: &TokenType) -> bool {
+    matches!(
+        token_type,
+        TokenType::INT
+            | TokenType::FLOAT
+            | TokenType::ID
+            | TokenType::RPAR
+            | TokenType::RBRACK
+            | TokenType::INC
+            | TokenType::DEC
+    )
+}
+
+/// `/** ... */` y `///` son documentación, pero no `/**/`, `/***...` ni `////...`
+pub fn is_doc_comment(lexemme: &str) -> bool {
+    if let Some(rest) = lexemme.strip_prefix("///") {
+        return !rest.starts_with('/');
+    }
+    match lexemme.strip_prefix("/**") {
+        Some(rest) => !rest.starts_with('*') && !rest.starts_with('/'),
+        None => false,
+    }
+}
+
+/// Texto de un comentario de documentación sin delimitadores ni `*` al inicio de cada línea
+pub fn doc_comment_text(lexemme: &str) -> String {
+    if let Some(line) = lexemme.strip_prefix("///") {
+        return line.trim().to_string();
+    }
+    let body = lexemme.strip_prefix("/**").unwrap_or(lexemme);
+    let body = body.strip_suffix('/').unwrap_or(body).trim_end_matches('*');
+    let lines: Vec<&str> = body
+        .lines()
+        .map(|line| {
+            let line = line.trim_start();
+            let line = line.strip_prefix('*').unwrap_or(line);
+            line.strip_prefix(' ').unwrap_or(line).trim_end()
+        })
+        .collect();
+    lines.join("\n").trim_matches('\n').to_string()
+}
+
+/// Mueve el cursor después de leer `c`, que ya se contó como una columna. `rest` empieza en `c`.
+/// `\n`, `\r\n` y un `\r` solo son saltos de línea; en `\r\n` el salto lo hace el `\n`.
+pub fn advance_line_break(c: char, rest: &str, cursor: &mut Cursor) {
+    match c {
+        '\r' if rest[1..].starts_with('\n') => cursor.col -= 1,
+        '\r' | '\n' => {
+            cursor.lin += 1;
+            cursor.col = 1;
+        }
+        _ => {}
+    }
+}
+
+/// Caracteres con los que no puede empezar ningún token
+pub fn is_unknown_symbol(c: char, options: &LexerOptions) -> bool {
+    !(['\r', '\n', '\t', ' '].contains(&c)
+        || (options.c_operators && (c == '&' || c == '|'))
+        || (options.unicode_identifiers && c.is_xid_start())
+        || c.is_ascii_alphanumeric()
+        || ['_', '-', '+', '/', '!', '<', '>', '='].contains(&c)
+        || options.config.symbol(c).is_some())
+}
+
+/// Error que acompaña a un token `NONE`
+pub fn unknown_symbols_error(token: &Token) -> Error {
+    Error {
+        start: token.start.clone(),
+        end: token.end.clone(),
+        message: unknown_symbols_message(&token.lexemme),
+        lexemme: token.lexemme.clone(),
+        suggestion: None,
+        severity: Severity::Error,
+        code: codes::UNKNOWN_SYMBOL.to_string(),
+    }
+}
+
+pub fn unknown_symbols_message(symbols: &str) -> String {
+    if symbols.chars().count() == 1 {
+        format!("Simbolo '{}' no permitido", symbols)
+    } else {
+        format!("Simbolos '{}' no permitidos", symbols)
+    }
+}
+
+/// Asignación compuesta que se forma al poner un '=' después del operador
+pub fn compound_assignment(operator: &TokenType) -> Option<TokenType> {
+    match operator {
+        TokenType::SUM => Some(TokenType::SUM_ASSIGN),
+        TokenType::MIN => Some(TokenType::MIN_ASSIGN),
+        TokenType::TIMES => Some(TokenType::TIMES_ASSIGN),
+        TokenType::DIV => Some(TokenType::DIV_ASSIGN),
+        TokenType::MODULUS => Some(TokenType::MODULUS_ASSIGN),
+        TokenType::POWER => Some(TokenType::POWER_ASSIGN),
+        _ => None,
+    }
+}
+
+/// Advertencia para una palabra reservada escrita con otras mayúsculas (`WHILE`, `Main`), con la
+/// forma correcta como sugerencia. Aplica tanto si se reconoció sin importar mayúsculas como si se
+/// quedó como `ID`.
+pub fn keyword_case_warning(token: &TokenRef, config: &LexerConfig) -> Option<Error> {
+    if config.keyword(&token.lexemme).is_some() {
+        return None;
+    }
+    let (canonical, _) = config.keyword_ignore_case(&token.lexemme)?;
+    Some(Error {
+        start: token.start.clone(),
+        end: token.end.clone(),
+        message: format!(
+            "La palabra reservada '{}' debería escribirse '{}'",
+            token.lexemme, canonical
+        ),
+        lexemme: token.lexemme.to_string(),
+        suggestion: Some(Suggestion {
+            message: format!("Escribe '{}'", canonical),
+            replacement: canonical.to_string(),
+            start: token.start.clone(),
+            end: token.end.clone(),
+        }),
+        severity: Severity::Warning,
+        code: codes::KEYWORD_CASE.to_string(),
+    })
 }
@@ -1,7 +1,9 @@
 use std::path::Path;
 
 use ::scanner::{
-    data::{Cursor, TokenType},
+    data::{Cursor, Severity, TokenType},
+    options::{LexerConfig, LexerOptions, LintOptions},
+    positions::PositionEncoding,
     *,
 };
 
@@ -9,7 +11,7 @@ use ::scanner::{
 fn it_tokenize_correctly() {
     let (tokens, errors) = tokenize(r"integer algo = 192");
     assert_eq!(tokens.len(), 4);
-    assert_eq!(tokens.get(0).unwrap().token_type, TokenType::INTEGER);
+    assert_eq!(tokens.first().unwrap().token_type, TokenType::INTEGER);
     assert_eq!(tokens.get(1).unwrap().token_type, TokenType::ID);
     assert_eq!(tokens.get(2).unwrap().token_type, TokenType::ASSIGN);
     assert_eq!(tokens.get(3).unwrap().token_type, TokenType::INT);
@@ -20,7 +22,7 @@ fn it_tokenize_correctly() {
 fn it_tokenize_relational() {
     let (tokens, errors) = tokenize(r"a=3 == 3;");
     assert_eq!(tokens.len(), 6);
-    assert_eq!(tokens.get(0).unwrap().token_type, TokenType::ID);
+    assert_eq!(tokens.first().unwrap().token_type, TokenType::ID);
     assert_eq!(tokens.get(1).unwrap().token_type, TokenType::ASSIGN);
     assert_eq!(tokens.get(2).unwrap().token_type, TokenType::INT);
     assert_eq!(tokens.get(3).unwrap().token_type, TokenType::EQ);
@@ -29,16 +31,35 @@ fn it_tokenize_relational() {
     assert_eq!(errors.len(), 0);
 }
 
+#[test]
+fn it_tokenize_signed_numbers() {
+    let options = LexerOptions {
+        signed_numbers: true,
+        ..Default::default()
+    };
+    let (tokens, errors) = tokenize_with(r"y=-2+3-1; z=(+4)*-1.5;", &options);
+    assert_eq!(errors.len(), 0);
+    let lexemmes: Vec<&str> = tokens.iter().map(|t| t.lexemme.as_str()).collect();
+    assert_eq!(
+        lexemmes,
+        vec!["y", "=", "-2", "+", "3", "-", "1", ";", "z", "=", "(", "+4", ")", "*", "-1.5", ";"]
+    );
+    assert_eq!(tokens.get(2).unwrap().token_type, TokenType::INT);
+    assert_eq!(tokens.get(14).unwrap().token_type, TokenType::FLOAT);
+    let (tokens, _) = tokenize(r"y=-2");
+    assert_eq!(tokens.len(), 4);
+}
+
 #[test]
 fn it_tokenize_errors() {
     let (tokens, errors) = tokenize(r"integer algo = 192.");
     assert_eq!(tokens.len(), 3);
-    assert_eq!(tokens.get(0).unwrap().token_type, TokenType::INTEGER);
+    assert_eq!(tokens.first().unwrap().token_type, TokenType::INTEGER);
     assert_eq!(tokens.get(1).unwrap().token_type, TokenType::ID);
     assert_eq!(tokens.get(2).unwrap().token_type, TokenType::ASSIGN);
     assert_eq!(errors.len(), 1);
-    assert_eq!(errors.get(0).unwrap().start, Cursor { col: 16, lin: 1 });
-    assert_eq!(errors.get(0).unwrap().end, Cursor { col: 20, lin: 1 })
+    assert_eq!(errors.first().unwrap().start, Cursor { col: 16, lin: 1 });
+    assert_eq!(errors.first().unwrap().end, Cursor { col: 20, lin: 1 })
 }
 
 #[test]
@@ -62,6 +83,156 @@ fn it_tokenize() {
     assert_eq!(tokens.len(), 5 + 5 + 5 + 6 + 6 + 7 + 5 + 1 + 5 + 8);
 }
 
+#[test]
+fn it_tokenize_switch_and_arrays() {
+    let (tokens, errors) = tokenize(
+        r"switch (a[i]) {
+        case 1: total += v.x; i -= 1;
+        case 2: b[0] ^= 2;
+    }",
+    );
+    assert_eq!(errors.len(), 0);
+    let types: Vec<TokenType> = tokens.iter().map(|t| t.token_type.clone()).collect();
+    assert_eq!(
+        types[..7],
+        [
+            TokenType::SWITCH,
+            TokenType::LPAR,
+            TokenType::ID,
+            TokenType::LBRACK,
+            TokenType::ID,
+            TokenType::RBRACK,
+            TokenType::RPAR,
+        ]
+    );
+    assert_eq!(
+        types[8..20],
+        [
+            TokenType::CASE,
+            TokenType::INT,
+            TokenType::COLON,
+            TokenType::ID,
+            TokenType::SUM_ASSIGN,
+            TokenType::ID,
+            TokenType::DOT,
+            TokenType::ID,
+            TokenType::SCOL,
+            TokenType::ID,
+            TokenType::MIN_ASSIGN,
+            TokenType::INT,
+        ]
+    );
+    assert!(types.contains(&TokenType::POWER_ASSIGN));
+
+    // un '.' antes de un dígito sigue siendo un número mal formado, no un DOT
+    let (tokens, errors) = tokenize("34.34.34.34");
+    assert_eq!(tokens.len(), 2);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].code, "E0007");
+    assert_eq!(errors[0].start, Cursor { col: 6, lin: 1 });
+}
+
+#[test]
+fn it_tokenize_c_operators() {
+    let (tokens, errors) = tokenize_with("if (a && !b || c)", &LexerOptions::c_like());
+    assert_eq!(errors.len(), 0);
+    let types: Vec<TokenType> = tokens.iter().map(|t| t.token_type.clone()).collect();
+    assert_eq!(
+        types,
+        vec![
+            TokenType::IF,
+            TokenType::LPAR,
+            TokenType::ID,
+            TokenType::AND,
+            TokenType::NEG,
+            TokenType::ID,
+            TokenType::OR,
+            TokenType::ID,
+            TokenType::RPAR
+        ]
+    );
+    assert_eq!(tokens.get(3).unwrap().lexemme, "&&");
+    assert_eq!(tokens.get(6).unwrap().lexemme, "||");
+    let (tokens, _) = tokenize_with("a and b or c", &LexerOptions::c_like());
+    assert_eq!(tokens.get(1).unwrap().token_type, TokenType::AND);
+    assert_eq!(tokens.get(3).unwrap().token_type, TokenType::OR);
+}
+
+#[test]
+fn it_suggests_fixes() {
+    let (tokens, errors) = tokenize("a := 2019. && b || c &&& d");
+    let suggestions: Vec<(&str, Cursor, Cursor)> = errors
+        .iter()
+        .filter_map(|e| e.suggestion.as_ref())
+        .map(|s| (s.replacement.as_str(), s.start.clone(), s.end.clone()))
+        .collect();
+    assert_eq!(
+        suggestions,
+        vec![
+            ("=", Cursor { col: 3, lin: 1 }, Cursor { col: 5, lin: 1 }),
+            (
+                "2019.0",
+                Cursor { col: 6, lin: 1 },
+                Cursor { col: 11, lin: 1 }
+            ),
+            (
+                "and",
+                Cursor { col: 12, lin: 1 },
+                Cursor { col: 14, lin: 1 }
+            ),
+            ("or", Cursor { col: 17, lin: 1 }, Cursor { col: 19, lin: 1 }),
+            (
+                "and",
+                Cursor { col: 22, lin: 1 },
+                Cursor { col: 24, lin: 1 }
+            ),
+        ]
+    );
+    assert_eq!(errors.len(), 9);
+    assert!(errors.get(3).unwrap().suggestion.is_none());
+
+    let options = LexerOptions {
+        none_tokens: true,
+        ..Default::default()
+    };
+    let (_, errors) = tokenize_with("a && b", &options);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors
+            .first()
+            .unwrap()
+            .suggestion
+            .as_ref()
+            .unwrap()
+            .replacement,
+        "and"
+    );
+
+    let real = tokens.iter().find(|t| t.lexemme == "a").unwrap();
+    assert!(suggestions::suggest_keyword(real, &LexerConfig::default()).is_none());
+    let (tokens, _) = tokenize("real x;");
+    let suggestion =
+        suggestions::suggest_keyword(tokens.first().unwrap(), &LexerConfig::default()).unwrap();
+    assert_eq!(suggestion.replacement, "double");
+    let (_, diagnostics) = tokenize_with_diagnostics("real x; cin y;", &LexerOptions::default());
+    let replacements: Vec<(&str, &str)> = diagnostics
+        .iter()
+        .map(|d| {
+            let suggestion = d.suggestion.as_ref().unwrap();
+            (d.code.as_str(), suggestion.replacement.as_str())
+        })
+        .collect();
+    assert_eq!(replacements, vec![("L0012", "double"), ("L0012", "stdin")]);
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+    // c-like no tiene palabra reservada para stdin y la de double es float
+    let (_, diagnostics) = tokenize_with_diagnostics("real x; cin y;", &LexerOptions::c_like());
+    let replacements: Vec<&str> = diagnostics
+        .iter()
+        .map(|d| d.suggestion.as_ref().unwrap().replacement.as_str())
+        .collect();
+    assert_eq!(replacements, vec!["float"]);
+}
+
 #[test]
 fn it_tokenize_file() {
     let path = Path::new(".").join("data").join("test.cat");
@@ -74,14 +245,36 @@ fn it_tokenize_file() {
     }
 }
 
+#[test]
+fn it_tokenize_doc_comments() {
+    let text = r"/// Contador
+    /// del ciclo
+    integer a = 0; // no es documentacion
+    /** esto es un comentario **/
+    // comentario normal
+    while (a < 10) {}
+    /// sin token";
+    let (tokens, errors, docs) = tokenize_with_docs(text, &LexerOptions::default());
+    assert_eq!(errors.len(), 0);
+    assert_eq!(docs.len(), 3);
+    assert_eq!(docs.first().unwrap().text, "Contador\ndel ciclo");
+    assert_eq!(docs.first().unwrap().token, Some(0));
+    assert_eq!(docs.get(1).unwrap().text, "esto es un comentario");
+    let documented = &tokens[docs.get(1).unwrap().token.unwrap()];
+    assert_eq!(documented.token_type, TokenType::WHILE);
+    assert_eq!(documented.start, Cursor { col: 5, lin: 6 });
+    assert_eq!(docs.get(2).unwrap().token, None);
+    assert_eq!(tokens, tokenize(text).0);
+}
+
 #[test]
 fn it_tokenize_file_errors() {
     let path = Path::new(".").join("data").join("test_errors.cat");
     let result = tokenize_file(path.to_str().unwrap());
     if let Ok((tokens, errors)) = result {
         assert_eq!(errors.len(), 5);
-        assert_eq!(errors.get(0).unwrap().start, Cursor { col: 5, lin: 2 });
-        assert_eq!(errors.get(0).unwrap().end, Cursor { col: 10, lin: 2 });
+        assert_eq!(errors.first().unwrap().start, Cursor { col: 5, lin: 2 });
+        assert_eq!(errors.first().unwrap().end, Cursor { col: 10, lin: 2 });
         assert_eq!(errors.get(1).unwrap().start, Cursor { col: 5, lin: 3 });
         assert_eq!(errors.get(1).unwrap().end, Cursor { col: 6, lin: 3 });
         assert_eq!(errors.get(2).unwrap().start, Cursor { col: 5, lin: 17 });
@@ -96,14 +289,263 @@ fn it_tokenize_file_errors() {
     }
 }
 
+#[test]
+fn it_tokenize_file_none_tokens() {
+    let path = Path::new(".").join("data").join("test_errors.cat");
+    let options = LexerOptions {
+        none_tokens: true,
+        ..Default::default()
+    };
+    let (tokens, errors) = tokenize_file_with(path.to_str().unwrap(), &options).unwrap();
+    assert_eq!(errors.len(), 4);
+    assert_eq!(errors.get(2).unwrap().start, Cursor { col: 5, lin: 17 });
+    assert_eq!(errors.get(2).unwrap().end, Cursor { col: 7, lin: 17 });
+    let none: Vec<&str> = tokens
+        .iter()
+        .filter(|t| t.token_type == TokenType::NONE)
+        .map(|t| t.lexemme.as_str())
+        .collect();
+    assert_eq!(none, vec!["?", "&&"]);
+    assert_eq!(
+        tokens.len(),
+        tokenize_file(path.to_str().unwrap()).unwrap().0.len() + 2
+    );
+}
+
+#[test]
+fn it_tokenize_file_dialects() {
+    let path = Path::new(".").join("data").join("tokens.cat");
+    let options = LexerOptions::for_dialect(dialect::Dialect::Legacy);
+    let (tokens, errors) = tokenize_file_with(path.to_str().unwrap(), &options).unwrap();
+    assert_eq!(errors.len(), 0);
+    assert_eq!(tokens.get(2).unwrap().token_type, TokenType::INTEGER);
+    let count =
+        |token_type: TokenType| tokens.iter().filter(|t| t.token_type == token_type).count();
+    assert_eq!(count(TokenType::DOUBLE), 1);
+    assert_eq!(count(TokenType::THEN), 2);
+    assert_eq!(count(TokenType::END), 2);
+    assert_eq!(count(TokenType::UNTIL), 1);
+    assert_eq!(count(TokenType::STDIN), 2);
+    assert_eq!(count(TokenType::STDOUT), 1);
+
+    let path = Path::new(".").join("data").join("test.cat");
+    let (tokens, errors) =
+        tokenize_file_with(path.to_str().unwrap(), &LexerOptions::c_like()).unwrap();
+    assert_eq!(errors.len(), 0);
+    assert_eq!(tokens.first().unwrap().token_type, TokenType::INTEGER);
+    assert!(tokens
+        .iter()
+        .any(|t| t.token_type == TokenType::STDOUT && t.lexemme == "print"));
+    let (tokens, _) = tokenize_file(path.to_str().unwrap()).unwrap();
+    assert_eq!(tokens.first().unwrap().token_type, TokenType::ID);
+}
+
+#[test]
+fn it_tokenize_case_insensitive_keywords() {
+    let text = "MAIN { While (x) { if (y) x = 1; } }";
+    let options = LexerOptions {
+        case_insensitive_keywords: true,
+        ..Default::default()
+    };
+    let (tokens, diagnostics) = tokenize_with_diagnostics(text, &options);
+    assert_eq!(tokens.first().unwrap().token_type, TokenType::MAIN);
+    assert_eq!(tokens.first().unwrap().lexemme, "MAIN");
+    assert_eq!(tokens.get(2).unwrap().token_type, TokenType::WHILE);
+    assert_eq!(diagnostics.len(), 2);
+    assert!(diagnostics.iter().all(|d| d.severity == Severity::Warning));
+    let warning = diagnostics.get(1).unwrap();
+    assert_eq!(warning.start, Cursor { col: 8, lin: 1 });
+    assert_eq!(warning.suggestion.as_ref().unwrap().replacement, "while");
+    assert!(tokenize_with(text, &options).1.is_empty());
+
+    let (tokens, diagnostics) = tokenize_with_diagnostics(text, &LexerOptions::default());
+    assert_eq!(tokens.first().unwrap().token_type, TokenType::ID);
+    assert!(diagnostics.is_empty());
+}
+
+#[test]
+fn it_tokenize_unicode_identifiers() {
+    let options = LexerOptions {
+        unicode_identifiers: true,
+        ..Default::default()
+    };
+    // "tamaño" con la 'ñ' descompuesta (n + tilde combinante)
+    let text = "integer año = tama\u{006E}\u{0303}o + tamaño;";
+    let (tokens, diagnostics) = tokenize_with_diagnostics(text, &options);
+    assert!(diagnostics.is_empty());
+    assert_eq!(tokens.get(1).unwrap().token_type, TokenType::ID);
+    assert_eq!(tokens.get(1).unwrap().lexemme, "año");
+    assert_eq!(tokens.get(3).unwrap().lexemme, "tamaño");
+    assert_eq!(tokens.get(3).unwrap().end, Cursor { col: 22, lin: 1 });
+    assert_eq!(
+        tokens.get(3).unwrap().lexemme,
+        tokens.get(5).unwrap().lexemme
+    );
+
+    let (_, errors) = tokenize(text);
+    assert_eq!(errors.first().unwrap().message, "Simbolo 'ñ' no permitido");
+
+    // 'а' cirílica
+    let (_, diagnostics) =
+        tokenize_with_diagnostics("a = 1; \u{0430} = 2; p\u{0430}ypal;", &options);
+    assert_eq!(diagnostics.len(), 2);
+    assert!(diagnostics.iter().all(|d| d.severity == Severity::Warning));
+    assert_eq!(
+        diagnostics.first().unwrap().start,
+        Cursor { col: 8, lin: 1 }
+    );
+    assert!(diagnostics
+        .first()
+        .unwrap()
+        .message
+        .contains("se confunde con 'a'"));
+    assert!(diagnostics.get(1).unwrap().message.contains("mezcla"));
+
+    // palabra reservada Unicode escrita descompuesta; se compara en NFC
+    let mut options = options.clone();
+    options
+        .config
+        .add_keyword(
+            "tama\u{006E}\u{0303}o",
+            TokenType::CUSTOM("TAMANO".to_string()),
+        )
+        .unwrap();
+    let (tokens, _) = tokenize_with("tamaño", &options);
+    assert_eq!(
+        tokens[0].token_type,
+        TokenType::CUSTOM("TAMANO".to_string())
+    );
+    assert!(options.config.add_keyword("1año", TokenType::IF).is_err());
+}
+
+#[test]
+fn it_reports_lint_warnings() {
+    let text = "integer contador_de_vueltas = 007; \n\t  x = 00.5;\n// x = x + 1;\n// comentario\nWHILE (x) {}\t";
+    let codes = |lints: LintOptions| -> Vec<(String, Cursor)> {
+        let options = LexerOptions {
+            lints,
+            ..Default::default()
+        };
+        let (_, diagnostics) = tokenize_with_diagnostics(text, &options);
+        assert!(diagnostics.iter().all(|d| d.severity == Severity::Warning));
+        diagnostics.into_iter().map(|d| (d.code, d.start)).collect()
+    };
+    assert!(codes(LintOptions::default()).is_empty());
+    assert_eq!(
+        codes(LintOptions {
+            max_identifier_length: Some(10),
+            ..Default::default()
+        }),
+        vec![("L0001".to_string(), Cursor { col: 9, lin: 1 })]
+    );
+    assert_eq!(
+        codes(LintOptions {
+            keyword_case: true,
+            ..Default::default()
+        }),
+        vec![("L0002".to_string(), Cursor { col: 1, lin: 5 })]
+    );
+    assert_eq!(
+        codes(LintOptions {
+            mixed_indentation: true,
+            ..Default::default()
+        }),
+        vec![("L0003".to_string(), Cursor { col: 1, lin: 2 })]
+    );
+    assert_eq!(
+        codes(LintOptions {
+            trailing_whitespace: true,
+            ..Default::default()
+        }),
+        vec![
+            ("L0004".to_string(), Cursor { col: 35, lin: 1 }),
+            ("L0004".to_string(), Cursor { col: 13, lin: 5 })
+        ]
+    );
+    assert_eq!(
+        codes(LintOptions {
+            leading_zeros: true,
+            ..Default::default()
+        }),
+        vec![
+            ("L0005".to_string(), Cursor { col: 31, lin: 1 }),
+            ("L0005".to_string(), Cursor { col: 8, lin: 2 })
+        ]
+    );
+    assert_eq!(
+        codes(LintOptions {
+            commented_out_code: true,
+            ..Default::default()
+        }),
+        vec![("L0006".to_string(), Cursor { col: 1, lin: 3 })]
+    );
+    assert_eq!(codes(LintOptions::all()).len(), 7);
+
+    let options = LexerOptions {
+        lints: LintOptions::all(),
+        ..Default::default()
+    };
+    let (_, diagnostics) = tokenize_with_diagnostics("x = 00.5;", &options);
+    let suggestion = diagnostics.first().unwrap().suggestion.as_ref().unwrap();
+    assert_eq!(suggestion.replacement, "0.5");
+    assert!(tokenize_with(text, &options).1.is_empty());
+}
+
+#[test]
+fn it_tokenize_custom_config() {
+    let mut config = LexerConfig::new(dialect::Dialect::Vanilla);
+    config
+        .add_keyword("print", TokenType::CUSTOM("PRINT".to_string()))
+        .unwrap();
+    config.add_keyword("real", TokenType::DOUBLE).unwrap();
+    config.add_keyword("main", TokenType::ID).unwrap();
+    assert_eq!(config.remove_keyword("stdout"), Some(TokenType::STDOUT));
+    config
+        .add_symbol('?', TokenType::CUSTOM("QUESTION".to_string()))
+        .unwrap();
+    config.add_symbol(';', TokenType::COMMA).unwrap();
+    assert_eq!(config.remove_symbol('%'), Some(TokenType::MODULUS));
+    assert!(config.add_symbol('+', TokenType::SUM).is_err());
+    assert!(config.add_keyword("no-valida", TokenType::IF).is_err());
+    assert_eq!(
+        config.keyword_ignore_case("PRINT"),
+        Some(("print", &TokenType::CUSTOM("PRINT".to_string())))
+    );
+    assert_eq!(config.keyword_ignore_case("StdOut"), None);
+
+    let options = LexerOptions {
+        config,
+        ..Default::default()
+    };
+    let (tokens, errors) = tokenize_with("print main real stdout a ? b; c % d", &options);
+    let types: Vec<TokenType> = tokens.iter().map(|t| t.token_type.clone()).collect();
+    assert_eq!(
+        types,
+        vec![
+            TokenType::CUSTOM("PRINT".to_string()),
+            TokenType::ID,
+            TokenType::DOUBLE,
+            TokenType::ID,
+            TokenType::ID,
+            TokenType::CUSTOM("QUESTION".to_string()),
+            TokenType::ID,
+            TokenType::COMMA,
+            TokenType::ID,
+            TokenType::ID,
+        ]
+    );
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors.first().unwrap().lexemme, "%");
+}
+
 #[test]
 fn it_tokenize_large_file() {
     let path = Path::new(".").join("data").join("test_large.cat");
     let result = tokenize_file(path.to_str().unwrap());
     if let Ok((tokens, errors)) = result {
         assert_eq!(errors.len(), 3);
-        assert_eq!(errors.get(0).unwrap().start, Cursor { col: 9, lin: 4 });
-        assert_eq!(errors.get(0).unwrap().end, Cursor { col: 10, lin: 4 });
+        assert_eq!(errors.first().unwrap().start, Cursor { col: 9, lin: 4 });
+        assert_eq!(errors.first().unwrap().end, Cursor { col: 10, lin: 4 });
         assert_eq!(errors.get(1).unwrap().start, Cursor { col: 25, lin: 4 });
         assert_eq!(errors.get(1).unwrap().end, Cursor { col: 28, lin: 4 });
         assert_eq!(errors.get(2).unwrap().start, Cursor { col: 6, lin: 5 });
@@ -158,3 +600,656 @@ fn it_tokenize_large_file() {
         println!("{}", e)
     }
 }
+
+#[test]
+fn it_suppresses_diagnostics_with_pragmas() {
+    let text = "// scanner: allow(L0005)\nx = 007;\ny = 007;\n/* scanner: off */\nz = 1 @ 2;\n/* scanner: on */\nw = 3 $ 4;\n// scanner: allow(E0001)\nv = 5 # 6; // scanner: silence\n";
+    let options = LexerOptions {
+        lints: LintOptions {
+            leading_zeros: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let (_, diagnostics) = tokenize_with_diagnostics(text, &options);
+    let codes: Vec<(String, Cursor)> = diagnostics.into_iter().map(|d| (d.code, d.start)).collect();
+    assert_eq!(
+        codes,
+        vec![
+            ("L0005".to_string(), Cursor { col: 5, lin: 3 }),
+            ("E0001".to_string(), Cursor { col: 7, lin: 7 }),
+            ("L0009".to_string(), Cursor { col: 12, lin: 9 }),
+        ]
+    );
+    let (_, errors) = tokenize_with(text, &LexerOptions::default());
+    assert_eq!(errors.len(), 1);
+}
+
+#[test]
+fn it_tokenize_header_directives() {
+    let text = "// vanilla: dialect=legacy, nested-comments, colour=red, warn=typos\nint x; /* a /* b */ c */";
+    let options = directives::effective_options(text, &LexerOptions::default());
+    assert_eq!(options.config.dialect, dialect::Dialect::Legacy);
+    assert!(options.nested_comments);
+    let (tokens, diagnostics) = tokenize_with_diagnostics(text, &LexerOptions::default());
+    assert_eq!(tokens[0].token_type, TokenType::INTEGER);
+    assert_eq!(tokens.len(), 3);
+    let codes: Vec<(String, Cursor)> = diagnostics.into_iter().map(|d| (d.code, d.start)).collect();
+    assert_eq!(
+        codes,
+        vec![
+            ("L0010".to_string(), Cursor { col: 46, lin: 1 }),
+            ("L0010".to_string(), Cursor { col: 58, lin: 1 }),
+        ]
+    );
+    // la cabecera termina también en un `\r` solo
+    let (tokens, diagnostics) = tokenize_with_diagnostics(
+        "// vanilla: dialect=legacy\rint x;",
+        &LexerOptions::default(),
+    );
+    assert_eq!(tokens[0].token_type, TokenType::INTEGER);
+    assert!(diagnostics.is_empty());
+    let plain = directives::effective_options("int x;", &LexerOptions::default());
+    assert_eq!(plain.config.dialect, dialect::Dialect::Vanilla);
+    // el dialecto de la cabecera conserva las palabras y símbolos que agregó quien llama
+    let mut options = LexerOptions::default();
+    options
+        .config
+        .add_keyword("print", TokenType::CUSTOM("PRINT".to_string()))
+        .unwrap();
+    options.config.remove_keyword("while");
+    options
+        .config
+        .add_symbol('@', TokenType::CUSTOM("AT".to_string()))
+        .unwrap();
+    let text = "// vanilla: dialect=legacy\nprint while real @";
+    let (tokens, errors) = tokenize_with(text, &options);
+    assert!(errors.is_empty());
+    let types: Vec<TokenType> = tokens.into_iter().map(|t| t.token_type).collect();
+    assert_eq!(
+        types,
+        vec![
+            TokenType::CUSTOM("PRINT".to_string()),
+            TokenType::ID,
+            TokenType::DOUBLE,
+            TokenType::CUSTOM("AT".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn it_tokenize_bom_and_line_endings() {
+    let text = "\u{FEFF}x = 1;\r\ny = 2;\r\nz = 3;\nw = 4;";
+    let format = line_endings::SourceFormat::detect(text);
+    assert!(format.bom && format.is_mixed());
+    assert_eq!(format.line_ending(), Some(line_endings::LineEnding::CrLf));
+    let (tokens, diagnostics) = tokenize_with_diagnostics(text, &LexerOptions::default());
+    assert_eq!(tokens.len(), 16);
+    assert_eq!(tokens[0].start, Cursor { col: 1, lin: 1 });
+    assert_eq!(tokens[12].start, Cursor { col: 1, lin: 4 });
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "L0011");
+    assert_eq!(diagnostics[0].start, Cursor { col: 7, lin: 3 });
+    assert_eq!(
+        line_endings::normalize_line_endings("a\r\nb\rc\n", line_endings::LineEnding::Lf),
+        "a\nb\nc\n"
+    );
+}
+
+#[test]
+fn it_tokenize_position_encodings() {
+    let text = "\tañ = 1;\n\t😀 x";
+    let columns = |position_encoding: PositionEncoding| -> (Vec<Cursor>, Cursor) {
+        let options = LexerOptions {
+            position_encoding,
+            unicode_identifiers: true,
+            ..Default::default()
+        };
+        let (tokens, errors) = tokenize_with(text, &options);
+        let starts = tokens.into_iter().map(|t| t.start).collect();
+        (starts, errors[0].start.clone())
+    };
+    let cursors = |cols: [i32; 6]| -> (Vec<Cursor>, Cursor) {
+        let lins = [1, 1, 1, 1, 2, 2];
+        let mut cursors: Vec<Cursor> = cols
+            .into_iter()
+            .zip(lins)
+            .map(|(col, lin)| Cursor { col, lin })
+            .collect();
+        let error = cursors.pop().unwrap();
+        (cursors, error)
+    };
+    assert_eq!(
+        columns(PositionEncoding::Chars),
+        cursors([2, 5, 7, 8, 4, 2])
+    );
+    assert_eq!(
+        columns(PositionEncoding::Bytes),
+        cursors([2, 6, 8, 9, 7, 2])
+    );
+    assert_eq!(
+        columns(PositionEncoding::Utf16),
+        cursors([2, 5, 7, 8, 5, 2])
+    );
+    assert_eq!(
+        columns(PositionEncoding::Display { tab_width: 4 }),
+        cursors([5, 8, 10, 11, 8, 5])
+    );
+}
+
+#[test]
+fn it_tokenize_invalid_utf8() {
+    let bytes = b"x = \xe9t\xe9;\n// caf\xe9\ny = 1;";
+    let (tokens, errors) = tokenize_bytes_with(bytes, &LexerOptions::default());
+    assert_eq!(tokens.len(), 8);
+    let positions: Vec<(&str, Cursor)> = errors
+        .iter()
+        .map(|e| (e.code.as_str(), e.start.clone()))
+        .collect();
+    assert_eq!(
+        positions,
+        vec![
+            ("E0004", Cursor { col: 5, lin: 1 }),
+            ("E0004", Cursor { col: 7, lin: 1 }),
+            ("E0004", Cursor { col: 7, lin: 2 }),
+        ]
+    );
+    let decoded = encoding::decode(bytes, encoding::SourceEncoding::Utf8);
+    assert_eq!(
+        tokenize_decoded_with_diagnostics(&decoded, &LexerOptions::default()),
+        tokenize_bytes_with_diagnostics(bytes, &LexerOptions::default())
+    );
+    let options = LexerOptions {
+        encoding: encoding::SourceEncoding::Latin1,
+        unicode_identifiers: true,
+        ..Default::default()
+    };
+    let (tokens, errors) = tokenize_bytes_with(bytes, &options);
+    assert!(errors.is_empty());
+    assert_eq!(tokens[2].lexemme, "été");
+
+    // en un token NONE los bytes inválidos solo se reportan como secuencia inválida
+    let options = LexerOptions {
+        none_tokens: true,
+        ..Default::default()
+    };
+    let (tokens, errors) = tokenize_bytes_with(b"x = @\xff#; \xfe;", &options);
+    assert_eq!(tokens[2].lexemme, "@\u{FFFD}#");
+    let messages: Vec<(&str, &str)> = errors
+        .iter()
+        .map(|e| (e.code.as_str(), e.message.as_str()))
+        .collect();
+    assert_eq!(
+        messages,
+        vec![
+            ("E0001", "Simbolos '@#' no permitidos"),
+            ("E0004", "Secuencia UTF-8 inválida: 0xFF"),
+            ("E0004", "Secuencia UTF-8 inválida: 0xFE"),
+        ]
+    );
+}
+
+#[test]
+fn it_tokenize_reader_chunks() {
+    let mut inputs: Vec<Vec<u8>> = [
+        "test.cat",
+        "test_errors.cat",
+        "tokens.cat",
+        "test_large.cat",
+    ]
+    .iter()
+    .map(|file| std::fs::read(Path::new("data").join(file)).unwrap())
+    .collect();
+    inputs.push(b"\xef\xbb\xbfx := 1 && caf\xc3\xa9\xe9;\r\n/* a\r\n\xff */ y <= 2.\rz".to_vec());
+    inputs.push(b"// vanilla: nested-comments\n/* a /* b */ */ 12.5".to_vec());
+    inputs.push(b"x = @\xff#; \xfe;".to_vec());
+    inputs.push(
+        b"x @ /* scanner: off */ # \xff /* scanner: on */ @\n// scanner: allow(E0001)\n$ \xfe y"
+            .to_vec(),
+    );
+    // solo el primer BOM se ignora
+    inputs.push("\u{FEFF}\u{FEFF}x".as_bytes().to_vec());
+    inputs.push(
+        "\tañ = 1;\t😀 @\u{FFFD}\r\n\t/* é\n\t*/ x\t== ~"
+            .as_bytes()
+            .to_vec(),
+    );
+    let none_tokens = LexerOptions {
+        none_tokens: true,
+        ..Default::default()
+    };
+    let variants = [
+        LexerOptions::default(),
+        none_tokens.clone(),
+        LexerOptions {
+            position_encoding: PositionEncoding::Bytes,
+            ..none_tokens.clone()
+        },
+        LexerOptions {
+            position_encoding: PositionEncoding::Display { tab_width: 4 },
+            ..Default::default()
+        },
+    ];
+    for bytes in inputs.iter() {
+        for options in variants.iter() {
+            let expected = tokenize_bytes_with(bytes, options);
+            for chunk_size in [1, 2, 3, 7, 64, 4096] {
+                let (mut tokens, mut errors) = (Vec::new(), Vec::new());
+                for item in reader::ReaderLexer::with_chunk_size(&bytes[..], options, chunk_size) {
+                    match item {
+                        Ok(token) => tokens.push(token),
+                        Err(error) => errors.push(error),
+                    }
+                }
+                assert_eq!(
+                    (tokens, errors),
+                    expected,
+                    "chunk size {} {:?}",
+                    chunk_size,
+                    options.position_encoding
+                );
+            }
+        }
+    }
+
+    let text = "// vanilla: dialect=legasy\nx = 1;";
+    let mut reader = reader::ReaderLexer::new(text.as_bytes(), &LexerOptions::default());
+    assert_eq!(reader.by_ref().count(), 4);
+    assert_eq!(
+        reader.warnings(),
+        tokenize_with_diagnostics(text, &LexerOptions::default()).1
+    );
+    assert_eq!(reader.warnings().len(), 1);
+}
+
+#[test]
+fn it_tokenize_borrowed_tokens() {
+    let path = Path::new("data").join("test_large.cat");
+    let contents = std::fs::read_to_string(path).unwrap();
+    let options = LexerOptions::default();
+    let (borrowed, errors) = tokenize_ref(&contents, &options);
+    assert!(borrowed
+        .iter()
+        .all(|token| matches!(token.lexemme, std::borrow::Cow::Borrowed(_))));
+    let owned: Vec<data::Token> = borrowed.into_iter().map(data::Token::from).collect();
+    assert_eq!((owned, errors), tokenize_with(&contents, &options));
+
+    // solo se copian los identificadores que cambian al normalizarse
+    let options = LexerOptions {
+        unicode_identifiers: true,
+        ..Default::default()
+    };
+    let text = "cafe\u{301} año";
+    let (tokens, _) = tokenize_ref(text, &options);
+    assert!(matches!(tokens[0].lexemme, std::borrow::Cow::Owned(_)));
+    assert_eq!(tokens[0].lexemme, "café");
+    assert!(matches!(
+        tokens[1].lexemme,
+        std::borrow::Cow::Borrowed("año")
+    ));
+    assert_eq!(tokens[1].to_token().as_token_ref(), tokens[1]);
+}
+
+#[test]
+fn it_interns_identifiers_across_files() {
+    let options = LexerOptions::default();
+    let mut interner = symbols::Interner::new();
+    let (first, _) = tokenize_interned("x = y + x;", &options, &mut interner);
+    let (second, _) = tokenize_interned("while (y) { z = 1; }", &options, &mut interner);
+    assert_eq!(first[0].symbol, first[4].symbol);
+    assert_eq!(first[2].symbol, second[2].symbol);
+    assert_eq!(first[1].symbol, None);
+    assert_eq!(second[0].symbol, None);
+    assert_eq!(first[0].lexemme, None);
+    assert_eq!(first[1].lexemme.as_deref(), Some("="));
+    assert_eq!(first[2].lexemme(&interner), Some("y"));
+    let y = interner.get("y").unwrap();
+    assert_eq!(interner.resolve(y), Some("y"));
+    assert_eq!(interner.count(y), Some(2));
+    let other = symbols::Interner::new();
+    assert_eq!(other.resolve(y), None);
+    assert_eq!(other.count(y), None);
+    let counts: Vec<(&str, usize)> = interner
+        .occurrences()
+        .map(|(_, name, count)| (name, count))
+        .collect();
+    assert_eq!(counts, vec![("x", 2), ("y", 2), ("z", 1)]);
+}
+
+#[test]
+fn it_lexes_line_by_line() {
+    let options = LexerOptions {
+        signed_numbers: true,
+        nested_comments: true,
+        ..Default::default()
+    };
+    for file in [
+        "test.cat",
+        "test_errors.cat",
+        "tokens.cat",
+        "test_large.cat",
+    ] {
+        let contents = std::fs::read_to_string(Path::new("data").join(file)).unwrap();
+        let contents = format!("{}\nx\n-1 /* a\n /* b */\n c */ - 2", contents);
+        for options in [LexerOptions::default(), options.clone()] {
+            let mut state = lines::LineState::default();
+            let mut significant = Vec::new();
+            for line in contents.lines() {
+                let (tokens, exiting) = lines::lex_line(line, state, &options);
+                significant.extend(
+                    tokens
+                        .into_iter()
+                        .filter(|token| {
+                            !matches!(
+                                token.token_type,
+                                TokenType::BLOCK_COMMENT
+                                    | TokenType::INLINE_COMMENT
+                                    | TokenType::DOC_COMMENT
+                                    | TokenType::NONE
+                            )
+                        })
+                        .map(|token| (token.token_type, token.lexemme)),
+                );
+                state = exiting;
+            }
+            let expected: Vec<(TokenType, String)> = tokenize_with(&contents, &options)
+                .0
+                .into_iter()
+                .map(|token| (token.token_type, token.lexemme))
+                .collect();
+            assert_eq!(significant, expected, "{}", file);
+        }
+    }
+
+    let options = LexerOptions {
+        nested_comments: true,
+        ..Default::default()
+    };
+    let (tokens, state) = lines::lex_line("x = 1; /* a /* b", Default::default(), &options);
+    assert_eq!(tokens.last().unwrap().token_type, TokenType::BLOCK_COMMENT);
+    assert_eq!(tokens.last().unwrap().lexemme, "/* a /* b");
+    assert_eq!(state.comment_depth, 2);
+    let (tokens, state) = lines::lex_line("c */ d */ y\n", state, &options);
+    assert_eq!(tokens[0].lexemme, "c */ d */");
+    assert_eq!(tokens[1].lexemme, "y");
+    assert_eq!(tokens[1].start, Cursor { col: 11, lin: 1 });
+    assert!(!state.in_comment() && state.after_operand);
+    assert_eq!(
+        lines::line_states("a /*\n*/ +", &options),
+        vec![
+            lines::LineState {
+                comment_depth: 1,
+                after_operand: true
+            },
+            lines::LineState::default(),
+        ]
+    );
+}
+
+#[test]
+fn it_retokenizes_edits_incrementally() {
+    // posición (línea, columna) del byte `offset`, contando saltos como el scanner
+    fn cursor_at(text: &str, offset: usize) -> Cursor {
+        let mut cursor = Cursor { col: 1, lin: 1 };
+        let mut chars = text[..offset].chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\n' => {
+                    cursor = Cursor {
+                        col: 1,
+                        lin: cursor.lin + 1,
+                    }
+                }
+                '\r' if chars.peek().is_none_or(|c| *c != '\n') => {
+                    cursor = Cursor {
+                        col: 1,
+                        lin: cursor.lin + 1,
+                    }
+                }
+                '\r' => {}
+                _ => cursor.col += 1,
+            }
+        }
+        cursor
+    }
+    let fragments = [
+        "",
+        "x",
+        "1",
+        ".",
+        "5",
+        "/*",
+        "*/",
+        "\n",
+        "\r\n",
+        "-",
+        "+ 2",
+        "&",
+        "12.",
+        " ",
+        "//",
+        "while",
+        "=",
+        "é",
+        "#",
+        ";\n",
+        "@",
+        "// scanner: allow(E0001)\n",
+        "/* scanner: off */",
+        "/* scanner: on */",
+        "// vanilla: dialect=legacy\n",
+    ];
+    let contents = std::fs::read_to_string(Path::new("data").join("test_large.cat")).unwrap();
+    let nested = LexerOptions {
+        signed_numbers: true,
+        nested_comments: true,
+        ..Default::default()
+    };
+    let mut seed: u64 = 7;
+    let mut random = |n: usize| {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (seed >> 33) as usize % n
+    };
+    for options in [LexerOptions::default(), nested] {
+        let mut document = incremental::Document::new(contents.clone(), options.clone());
+        for _ in 0..300 {
+            let text = document.text();
+            let boundary = |offset: usize| {
+                let mut offset = offset;
+                while !text.is_char_boundary(offset)
+                    || (text[..offset].ends_with('\r') && text[offset..].starts_with('\n'))
+                {
+                    offset -= 1;
+                }
+                offset
+            };
+            let start = boundary(random(text.len() + 1));
+            let end = boundary((start + random(12)).min(text.len()));
+            let edit = incremental::Edit {
+                start: cursor_at(text, start),
+                end: cursor_at(text, end),
+                text: fragments[random(fragments.len())].to_string(),
+            };
+            document.apply(&edit).unwrap();
+            let (tokens, errors) = tokenize_with(document.text(), &options);
+            assert_eq!(document.tokens(), tokens, "{:?}", edit);
+            assert_eq!(document.errors(), errors, "{:?}", edit);
+        }
+    }
+
+    // una edición en medio del archivo solo vuelve a escanear unos cuantos tokens
+    let mut document = incremental::Document::new(contents.clone(), LexerOptions::default());
+    let total = document.tokens().len();
+    let lin = contents.lines().count() as i32 / 2;
+    let edit = incremental::Edit {
+        start: Cursor { col: 1, lin },
+        end: Cursor { col: 1, lin },
+        text: "nuevo = 1;\n".to_string(),
+    };
+    let relexed = document.apply(&edit).unwrap();
+    assert!(relexed.len() < 10, "{:?}", relexed);
+    assert_eq!(document.tokens().len(), total + 4);
+
+    // ni las ediciones en la primera línea fuera de la directiva ni los pragmas de otras líneas
+    // obligan a escanear todo
+    let text = "x = 1; y = 2; z = 3;\n// scanner: allow(E0001)\n@ w = 4; v = 5;\n";
+    let mut document = incremental::Document::new(text.to_string(), LexerOptions::default());
+    let edit = incremental::Edit {
+        start: Cursor { col: 12, lin: 1 },
+        end: Cursor { col: 13, lin: 1 },
+        text: "20".to_string(),
+    };
+    assert_eq!(document.apply(&edit).unwrap(), 6..7);
+    assert!(document.errors().is_empty());
+    // al bajar el `@` una línea el pragma ya no lo suprime
+    let edit = incremental::Edit {
+        start: Cursor { col: 1, lin: 3 },
+        end: Cursor { col: 1, lin: 3 },
+        text: "\n".to_string(),
+    };
+    assert!(document.apply(&edit).unwrap().len() < 4);
+    assert_eq!(
+        document.errors(),
+        tokenize_with(document.text(), &LexerOptions::default()).1
+    );
+    assert_eq!(document.errors().len(), 1);
+
+    assert!(document
+        .apply(&incremental::Edit {
+            start: Cursor {
+                col: 1,
+                lin: 10_000
+            },
+            end: Cursor {
+                col: 1,
+                lin: 10_000
+            },
+            text: String::new(),
+        })
+        .is_err());
+}
+
+#[test]
+fn it_streams_tokens_with_lookahead() {
+    let contents = std::fs::read_to_string(Path::new("data").join("test_large.cat")).unwrap();
+    let options = LexerOptions::default();
+    let mut stream = stream::TokenStream::new(&contents, &options);
+    let tokens: Vec<data::Token> = stream.by_ref().collect();
+    assert_eq!(
+        (tokens.clone(), stream.errors().to_vec()),
+        tokenize_with(&contents, &options)
+    );
+    assert_eq!(
+        stream::TokenStream::from_tokens(tokens).count(),
+        tokenize(&contents).0.len()
+    );
+
+    let text = "x = /* valor */ 1;\n// fin\nwhile";
+    let mut stream = stream::TokenStream::new(text, &options);
+    assert_eq!(stream.peek(2).unwrap().lexemme, "1");
+    let start = stream.mark();
+    assert_eq!(stream.expect(TokenType::ID).unwrap().lexemme, "x");
+    assert_eq!(stream.next().unwrap().token_type, TokenType::ASSIGN);
+    let error = stream.expect(TokenType::ID).unwrap_err();
+    assert_eq!(error.code, "E0005");
+    assert_eq!(error.start, Cursor { col: 17, lin: 1 });
+    assert_eq!(stream.cursor(), Cursor { col: 17, lin: 1 });
+    stream.reset(start);
+    assert_eq!(stream.peek(0).unwrap().lexemme, "x");
+    assert_eq!(stream.nth(4).unwrap().token_type, TokenType::WHILE);
+    assert!(stream.peek(0).is_none());
+    let error = stream.expect(TokenType::SCOL).unwrap_err();
+    assert_eq!(error.start, Cursor { col: 6, lin: 3 });
+    let text = "x @ /* scanner: off */ # /* scanner: on */ @\n// scanner: allow(E0001)\n$ y";
+    let mut stream = stream::TokenStream::new(text, &options);
+    assert_eq!(stream.by_ref().count(), 2);
+    assert_eq!(stream.errors(), tokenize_with(text, &options).1);
+    assert_eq!(stream.errors().len(), 2);
+
+    let text = "// vanilla: dialect=legasy\nx = 1;";
+    let mut stream = stream::TokenStream::new(text, &options);
+    assert_eq!(stream.by_ref().count(), 4);
+    assert_eq!(
+        stream.warnings(),
+        tokenize_with_diagnostics(text, &options).1
+    );
+    assert_eq!(stream.warnings().len(), 1);
+
+    // tokens, errores y sugerencias en la misma unidad que `tokenize_with`
+    let text = "\tañó = @ 1;\n  x\t& y # z\n\té";
+    for (position_encoding, end) in [
+        (PositionEncoding::Bytes, 4),
+        (PositionEncoding::Utf16, 3),
+        (PositionEncoding::Display { tab_width: 4 }, 6),
+    ] {
+        let options = LexerOptions {
+            position_encoding,
+            ..Default::default()
+        };
+        let mut stream = stream::TokenStream::new(text, &options);
+        let tokens: Vec<data::Token> = stream.by_ref().collect();
+        assert_eq!(
+            (tokens, stream.errors().to_vec()),
+            tokenize_with(text, &options)
+        );
+        assert_eq!(stream.cursor(), Cursor { col: end, lin: 3 });
+    }
+}
+
+#[test]
+fn it_checks_bracket_balance() {
+    let contents = std::fs::read_to_string(Path::new("data").join("test_large.cat")).unwrap();
+    let errors = brackets::check_brackets(&tokenize(&contents).0);
+    let found: Vec<(&str, Cursor, &str)> = errors
+        .iter()
+        .map(|error| {
+            let suggestion = error.suggestion.as_ref().unwrap();
+            (
+                error.lexemme.as_str(),
+                error.start.clone(),
+                suggestion.replacement.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (")", Cursor { col: 21, lin: 4 }, ""),
+            ("{", Cursor { col: 24, lin: 4 }, "}"),
+        ]
+    );
+    assert!(errors.iter().all(|error| error.code == "E0006"));
+
+    // el paréntesis sin cerrar se cierra antes del bloque
+    let errors = brackets::check_brackets(&tokenize("while (x > 3 { y = (1; }").0);
+    let repairs: Vec<(String, Cursor)> = errors
+        .iter()
+        .map(|error| {
+            let suggestion = error.suggestion.clone().unwrap();
+            (suggestion.replacement, suggestion.start)
+        })
+        .collect();
+    assert_eq!(
+        repairs,
+        vec![
+            (")".to_string(), Cursor { col: 14, lin: 1 }),
+            (")".to_string(), Cursor { col: 22, lin: 1 }),
+        ]
+    );
+    let errors = brackets::check_brackets(&tokenize("{ f(x}").0);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].message,
+        "El '(' no se cierra antes del '}' (línea 1, columna 6)"
+    );
+    let errors = brackets::check_brackets(&tokenize("f(x}").0);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].message,
+        "El '}' cierra el '(' (línea 1, columna 2)"
+    );
+    assert_eq!(errors[0].suggestion.as_ref().unwrap().replacement, ")");
+    assert!(brackets::check_brackets(&tokenize("{ f(x); }").0).is_empty());
+}